@@ -0,0 +1,49 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::env;
+use std::path;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+
+    let manifest_dir = path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    // written to OUT_DIR so that building never modifies the source tree; tests/ffi.rs checks that the copy in
+    // include/ matches it
+    let header_path = path::Path::new(&env::var_os("OUT_DIR").unwrap()).join("bpfasm.h");
+
+    let config = cbindgen::Config {
+        language: cbindgen::Language::C,
+        header: Some(
+            "/* Copyright © Alex Forster <alex@alexforster.com> */\n/* SPDX-License-Identifier: MIT OR Apache-2.0 */"
+                .into(),
+        ),
+        autogen_warning: Some("/* Generated from src/lib.rs by build.rs; do not edit */".into()),
+        include_guard: Some("BPFASM_H".into()),
+        sys_includes: vec!["stddef.h".into(), "stdint.h".into()],
+        no_includes: true,
+        after_includes: Some(
+            "\n#if defined(__linux__)\n#include <linux/filter.h>\n#else\n\
+             struct sock_filter {\n    uint16_t code;\n    uint8_t jt;\n    uint8_t jf;\n    uint32_t k;\n};\n#endif"
+                .into(),
+        ),
+        style: cbindgen::Style::Tag,
+        cpp_compat: true,
+        documentation_style: cbindgen::DocumentationStyle::C,
+        usize_is_size_t: true,
+        // sock_filter comes from <linux/filter.h>, or from the fallback definition above
+        export: cbindgen::ExportConfig {
+            exclude: vec!["sock_filter".into()],
+            rename: [("sock_filter".into(), "struct sock_filter".into())].into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    cbindgen::Builder::new()
+        .with_crate(manifest_dir)
+        .with_config(config)
+        .generate()
+        .expect("could not generate bpfasm.h")
+        .write_to_file(header_path);
+}
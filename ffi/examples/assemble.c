@@ -0,0 +1,46 @@
+/* Copyright © Alex Forster <alex@alexforster.com> */
+/* SPDX-License-Identifier: MIT OR Apache-2.0 */
+
+/* Assembles a filter and prints it in the same format as `tcpdump -ddd`:
+ *
+ *     cargo build -p bpfasm-ffi --release
+ *     cc -Iffi/include ffi/examples/assemble.c target/release/libbpfasm_ffi.a -lpthread -ldl -lm -o assemble
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "bpfasm.h"
+
+int main(void) {
+    const char *source = "ldh [12]\n"
+                         "jne #0x800, drop\n"
+                         "ret #-1\n"
+                         "drop: ret #0\n";
+
+    struct sock_filter filter[64];
+    size_t count = sizeof(filter) / sizeof(filter[0]);
+    struct bpfasm_error *error = NULL;
+
+    switch (bpfasm_assemble(source, strlen(source), filter, &count, &error)) {
+    case BPFASM_OK:
+        break;
+    case BPFASM_ERROR_ASSEMBLE:
+        fprintf(stderr, "line %zu, column %zu:\n%s\n", bpfasm_error_line(error), bpfasm_error_column(error),
+                bpfasm_error_message(error));
+        bpfasm_error_free(error);
+        return 1;
+    case BPFASM_ERROR_CAPACITY:
+        fprintf(stderr, "filter needs %zu instructions\n", count);
+        return 1;
+    default:
+        return 1;
+    }
+
+    printf("%zu\n", count);
+    for (size_t i = 0; i < count; i++) {
+        printf("%u %u %u %u\n", filter[i].code, filter[i].jt, filter[i].jf, filter[i].k);
+    }
+
+    return 0;
+}
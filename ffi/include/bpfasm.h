@@ -0,0 +1,147 @@
+/* Copyright © Alex Forster <alex@alexforster.com> */
+/* SPDX-License-Identifier: MIT OR Apache-2.0 */
+
+#ifndef BPFASM_H
+#define BPFASM_H
+
+/* Generated from src/lib.rs by build.rs; do not edit */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#if defined(__linux__)
+#include <linux/filter.h>
+#else
+struct sock_filter {
+    uint16_t code;
+    uint8_t jt;
+    uint8_t jf;
+    uint32_t k;
+};
+#endif
+
+/*
+ The program was assembled and written to the filter
+ */
+#define BPFASM_OK 0
+
+/*
+ The source could not be assembled; the error describes why
+ */
+#define BPFASM_ERROR_ASSEMBLE -1
+
+/*
+ The filter is too small; the count holds the number of instructions needed
+ */
+#define BPFASM_ERROR_CAPACITY -2
+
+/*
+ A required pointer is null, or the source is not valid UTF-8
+ */
+#define BPFASM_ERROR_ARGUMENT -3
+
+/*
+ There is no extension with the given name
+ */
+#define BPFASM_ERROR_NOT_FOUND -4
+
+/*
+ Why a source could not be assembled
+ */
+struct bpfasm_error;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Assembles `length` bytes of UTF-8 `source` for Linux socket filters. `*count` holds the capacity of `filter`
+ in instructions, and is set to the number of instructions in the program. Returns `BPFASM_OK` if the program was
+ written to `filter`, `BPFASM_ERROR_CAPACITY` if it did not fit, in which case nothing is written, or
+ `BPFASM_ERROR_ASSEMBLE` if it could not be assembled. In that case, if `error` is not null, `*error` is set to a
+ description that must be freed with `bpfasm_error_free`.
+
+ # Safety
+
+ `source` must point to `length` readable bytes, `count` must be a valid pointer, `filter` must point to `*count`
+ writable instructions, and `error` must be null or a valid pointer.
+ */
+int32_t bpfasm_assemble(const char *source,
+                        size_t length,
+                        struct sock_filter *filter,
+                        size_t *count,
+                        struct bpfasm_error **error);
+
+/*
+ Frees an error returned by `bpfasm_assemble`. Does nothing if `error` is null.
+
+ # Safety
+
+ `error` must be null or an error returned by `bpfasm_assemble` that has not been freed.
+ */
+void bpfasm_error_free(struct bpfasm_error *error);
+
+/*
+ Returns the full diagnostic, including the offending source line. It lives as long as `error`.
+
+ # Safety
+
+ `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+ */
+const char *bpfasm_error_message(const struct bpfasm_error *error);
+
+/*
+ Returns the name of the included file the error is in, or null if it is in the source passed to
+ `bpfasm_assemble`. It lives as long as `error`.
+
+ # Safety
+
+ `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+ */
+const char *bpfasm_error_path(const struct bpfasm_error *error);
+
+/*
+ Returns the line of the error, counting from 1
+
+ # Safety
+
+ `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+ */
+size_t bpfasm_error_line(const struct bpfasm_error *error);
+
+/*
+ Returns the column of the error, counting from 1
+
+ # Safety
+
+ `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+ */
+size_t bpfasm_error_column(const struct bpfasm_error *error);
+
+/*
+ Looks up the default Linux extension called `name`, such as `"proto"`. Returns `BPFASM_OK` and sets `*k` to its
+ value if it exists, or returns `BPFASM_ERROR_NOT_FOUND` if it does not.
+
+ # Safety
+
+ `name` must be a null-terminated string, and `k` must be a valid pointer.
+ */
+int32_t bpfasm_extension_lookup(const char *name,
+                                uint32_t *k);
+
+/*
+ Returns the number of default Linux extensions
+ */
+size_t bpfasm_extension_count(void);
+
+/*
+ Returns the name of the default Linux extension at `index`, in order of name, or null if `index` is out of range.
+ The name lives as long as the program.
+ */
+const char *bpfasm_extension_name(size_t index);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* BPFASM_H */
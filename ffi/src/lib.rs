@@ -0,0 +1,209 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! C interface to the assembler. `include/bpfasm.h` is a checked-in copy of the header that `build.rs` generates
+//! from this file.
+
+use std::ffi;
+use std::ptr;
+use std::slice;
+use std::sync;
+
+use bpfasm::{Assembler, AssemblerError};
+
+/// The program was assembled and written to the filter
+pub const BPFASM_OK: i32 = 0;
+/// The source could not be assembled; the error describes why
+pub const BPFASM_ERROR_ASSEMBLE: i32 = -1;
+/// The filter is too small; the count holds the number of instructions needed
+pub const BPFASM_ERROR_CAPACITY: i32 = -2;
+/// A required pointer is null, or the source is not valid UTF-8
+pub const BPFASM_ERROR_ARGUMENT: i32 = -3;
+/// There is no extension with the given name
+pub const BPFASM_ERROR_NOT_FOUND: i32 = -4;
+
+/// A classic BPF instruction, laid out like the kernel's `struct sock_filter`
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub struct sock_filter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+/// Why a source could not be assembled
+#[allow(non_camel_case_types)]
+pub struct bpfasm_error {
+    message: ffi::CString,
+    path: Option<ffi::CString>,
+    line: usize,
+    column: usize,
+}
+
+impl From<AssemblerError> for bpfasm_error {
+    fn from(error: AssemblerError) -> Self {
+        let located = match &error {
+            AssemblerError::Parse(error) => error,
+            AssemblerError::Macro { call, .. } => call,
+        };
+        let (line, column) = match located.line_col {
+            pest::error::LineColLocation::Pos(position) => position,
+            pest::error::LineColLocation::Span(start, _) => start,
+        };
+        let path = located.path().map(|path| ffi::CString::new(path.replace('\0', "")).unwrap());
+
+        bpfasm_error { message: ffi::CString::new(error.to_string().replace('\0', "")).unwrap(), path, line, column }
+    }
+}
+
+/// Assembles `length` bytes of UTF-8 `source` for Linux socket filters. `*count` holds the capacity of `filter`
+/// in instructions, and is set to the number of instructions in the program. Returns `BPFASM_OK` if the program was
+/// written to `filter`, `BPFASM_ERROR_CAPACITY` if it did not fit, in which case nothing is written, or
+/// `BPFASM_ERROR_ASSEMBLE` if it could not be assembled. In that case, if `error` is not null, `*error` is set to a
+/// description that must be freed with `bpfasm_error_free`.
+///
+/// # Safety
+///
+/// `source` must point to `length` readable bytes, `count` must be a valid pointer, `filter` must point to `*count`
+/// writable instructions, and `error` must be null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bpfasm_assemble(
+    source: *const ffi::c_char,
+    length: usize,
+    filter: *mut sock_filter,
+    count: *mut usize,
+    error: *mut *mut bpfasm_error,
+) -> i32 {
+    if !error.is_null() {
+        *error = ptr::null_mut();
+    }
+    if source.is_null() || count.is_null() || (filter.is_null() && *count != 0) {
+        return BPFASM_ERROR_ARGUMENT;
+    }
+    let source = match std::str::from_utf8(slice::from_raw_parts(source.cast::<u8>(), length)) {
+        Ok(source) => source,
+        Err(_) => return BPFASM_ERROR_ARGUMENT,
+    };
+
+    let insns = match Assembler::default().assemble(source) {
+        Ok(insns) => insns,
+        Err(assembler_error) => {
+            if !error.is_null() {
+                *error = Box::into_raw(Box::new(bpfasm_error::from(assembler_error)));
+            }
+            return BPFASM_ERROR_ASSEMBLE;
+        }
+    };
+
+    let capacity = *count;
+    *count = insns.len();
+    if insns.len() > capacity {
+        return BPFASM_ERROR_CAPACITY;
+    }
+    for (i, insn) in insns.iter().enumerate() {
+        *filter.add(i) = sock_filter { code: insn.code, jt: insn.jt, jf: insn.jf, k: insn.k };
+    }
+
+    BPFASM_OK
+}
+
+/// Frees an error returned by `bpfasm_assemble`. Does nothing if `error` is null.
+///
+/// # Safety
+///
+/// `error` must be null or an error returned by `bpfasm_assemble` that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bpfasm_error_free(error: *mut bpfasm_error) {
+    if !error.is_null() {
+        drop(Box::from_raw(error));
+    }
+}
+
+/// Returns the full diagnostic, including the offending source line. It lives as long as `error`.
+///
+/// # Safety
+///
+/// `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bpfasm_error_message(error: *const bpfasm_error) -> *const ffi::c_char {
+    (*error).message.as_ptr()
+}
+
+/// Returns the name of the included file the error is in, or null if it is in the source passed to
+/// `bpfasm_assemble`. It lives as long as `error`.
+///
+/// # Safety
+///
+/// `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bpfasm_error_path(error: *const bpfasm_error) -> *const ffi::c_char {
+    (*error).path.as_ref().map_or(ptr::null(), |path| path.as_ptr())
+}
+
+/// Returns the line of the error, counting from 1
+///
+/// # Safety
+///
+/// `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bpfasm_error_line(error: *const bpfasm_error) -> usize {
+    (*error).line
+}
+
+/// Returns the column of the error, counting from 1
+///
+/// # Safety
+///
+/// `error` must be an error returned by `bpfasm_assemble` that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bpfasm_error_column(error: *const bpfasm_error) -> usize {
+    (*error).column
+}
+
+/// Names and values of the default Linux extensions, sorted by name
+fn extensions() -> &'static [(ffi::CString, u32)] {
+    static EXTENSIONS: sync::OnceLock<Vec<(ffi::CString, u32)>> = sync::OnceLock::new();
+    EXTENSIONS.get_or_init(|| {
+        let mut extensions = bpfasm::extensions::linux()
+            .into_iter()
+            .map(|(name, k)| (ffi::CString::new(name).unwrap(), k))
+            .collect::<Vec<_>>();
+        extensions.sort();
+        extensions
+    })
+}
+
+/// Looks up the default Linux extension called `name`, such as `"proto"`. Returns `BPFASM_OK` and sets `*k` to its
+/// value if it exists, or returns `BPFASM_ERROR_NOT_FOUND` if it does not.
+///
+/// # Safety
+///
+/// `name` must be a null-terminated string, and `k` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bpfasm_extension_lookup(name: *const ffi::c_char, k: *mut u32) -> i32 {
+    if name.is_null() || k.is_null() {
+        return BPFASM_ERROR_ARGUMENT;
+    }
+    let name = ffi::CStr::from_ptr(name);
+    match extensions().iter().find(|(candidate, _)| candidate.as_c_str() == name) {
+        Some((_, value)) => {
+            *k = *value;
+            BPFASM_OK
+        }
+        None => BPFASM_ERROR_NOT_FOUND,
+    }
+}
+
+/// Returns the number of default Linux extensions
+#[no_mangle]
+pub extern "C" fn bpfasm_extension_count() -> usize {
+    extensions().len()
+}
+
+/// Returns the name of the default Linux extension at `index`, in order of name, or null if `index` is out of range.
+/// The name lives as long as the program.
+#[no_mangle]
+pub extern "C" fn bpfasm_extension_name(index: usize) -> *const ffi::c_char {
+    extensions().get(index).map_or(ptr::null(), |(name, _)| name.as_ptr())
+}
@@ -0,0 +1,82 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::ffi;
+use std::fs;
+use std::ptr;
+
+use bpfasm_ffi::*;
+
+#[test]
+fn test_assemble() {
+    let source = "ldh [12]\njne #0x800, drop\nret #-1\ndrop: ret #0\n";
+    let mut filter = [sock_filter { code: 0, jt: 0, jf: 0, k: 0 }, sock_filter { code: 0, jt: 0, jf: 0, k: 0 }];
+    let mut count = filter.len();
+    let mut error = ptr::null_mut();
+
+    let status =
+        unsafe { bpfasm_assemble(source.as_ptr().cast(), source.len(), filter.as_mut_ptr(), &mut count, &mut error) };
+    assert_eq!((status, count), (BPFASM_ERROR_CAPACITY, 4));
+    assert!(error.is_null());
+
+    let mut filter = Vec::with_capacity(count);
+    filter.resize_with(count, || sock_filter { code: 0, jt: 0, jf: 0, k: 0 });
+    let status =
+        unsafe { bpfasm_assemble(source.as_ptr().cast(), source.len(), filter.as_mut_ptr(), &mut count, &mut error) };
+    assert_eq!((status, count), (BPFASM_OK, 4));
+    assert_eq!(
+        filter.iter().map(|insn| (insn.code, insn.jt, insn.jf, insn.k)).collect::<Vec<_>>(),
+        [(0x28, 0, 0, 12), (0x15, 0, 1, 0x800), (0x06, 0, 0, u32::MAX), (0x06, 0, 0, 0)]
+    );
+}
+
+#[test]
+fn test_error() {
+    let source = "ldh [12]\njne #0x800, nowhere\nret #0\n";
+    let mut count = 0;
+    let mut error = ptr::null_mut();
+
+    let status =
+        unsafe { bpfasm_assemble(source.as_ptr().cast(), source.len(), ptr::null_mut(), &mut count, &mut error) };
+    assert_eq!(status, BPFASM_ERROR_ASSEMBLE);
+    assert!(!error.is_null());
+    unsafe {
+        let message = ffi::CStr::from_ptr(bpfasm_error_message(error)).to_str().unwrap();
+        assert!(message.contains("undeclared label \"nowhere\""), "{}", message);
+        assert!(bpfasm_error_path(error).is_null());
+        assert_eq!((bpfasm_error_line(error), bpfasm_error_column(error)), (2, 13));
+        bpfasm_error_free(error);
+    }
+
+    let invalid = [0xffu8, 0xfe];
+    let status = unsafe {
+        bpfasm_assemble(invalid.as_ptr().cast(), invalid.len(), ptr::null_mut(), &mut count, ptr::null_mut())
+    };
+    assert_eq!(status, BPFASM_ERROR_ARGUMENT);
+}
+
+#[test]
+fn test_extensions() {
+    let mut k = 0;
+    assert_eq!(unsafe { bpfasm_extension_lookup(c"proto".as_ptr(), &mut k) }, BPFASM_OK);
+    assert_eq!(k, 0xfffff000);
+    assert_eq!(unsafe { bpfasm_extension_lookup(c"nope".as_ptr(), &mut k) }, BPFASM_ERROR_NOT_FOUND);
+
+    let names = (0..bpfasm_extension_count())
+        .map(|i| unsafe { ffi::CStr::from_ptr(bpfasm_extension_name(i)) }.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names.len(), bpfasm::extensions::linux().len());
+    assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(bpfasm_extension_name(names.len()).is_null());
+}
+
+#[test]
+fn test_header() {
+    let generated = concat!(env!("OUT_DIR"), "/bpfasm.h");
+    let checked_in = concat!(env!("CARGO_MANIFEST_DIR"), "/include/bpfasm.h");
+    assert!(
+        fs::read(generated).unwrap() == fs::read(checked_in).unwrap(),
+        "include/bpfasm.h is out of date; copy {} over it",
+        generated
+    );
+}
@@ -0,0 +1,171 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::io;
+use std::path;
+use std::sync;
+
+use bpfasm::{Assembler, AssemblerError, Instruction, Source, SourceResolver};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+
+/// Assembles the filter written inside the macro invocation and expands to a constant `[bpfasm::Instruction; N]`.
+/// Assembler errors are reported as compile errors at the offending token.
+///
+/// ```ignore
+/// const FILTER: [bpfasm::Instruction; 2] = bpfasm_macros::bpf! {
+///     ldh [12]
+///     ret a
+/// };
+/// ```
+#[proc_macro]
+pub fn bpf(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let mut text = Text::default();
+    text.push_stream(input.into());
+
+    match Assembler::default().assemble(&text.source) {
+        Ok(insns) => expand(&insns, TokenStream::new()),
+        Err(error) => {
+            let message = match &error {
+                AssemblerError::Parse(error) if error.path().is_none() => error.variant.message().into_owned(),
+                error => error.to_string(),
+            };
+            syn::Error::new(text.span(&error), message).to_compile_error()
+        }
+    }
+    .into()
+}
+
+/// Assembles the file at the given path, relative to the directory containing the crate's `Cargo.toml`, and expands
+/// to a constant `[bpfasm::Instruction; N]`. `.include` directives are resolved relative to the including file, and the
+/// crate is rebuilt whenever any of the files changes.
+///
+/// ```ignore
+/// static FILTER: &[bpfasm::Instruction] = &bpfasm_macros::bpf_file!("filters/tcp.bpf");
+/// ```
+#[proc_macro]
+pub fn bpf_file(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let literal = syn::parse_macro_input!(input as syn::LitStr);
+
+    let manifest_directory = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = path::Path::new(&manifest_directory).join(literal.value());
+    let (path, source) = match path.canonicalize().and_then(|path| Ok((std::fs::read_to_string(&path)?, path))) {
+        Ok((source, path)) => (path.to_string_lossy().into_owned(), source),
+        Err(error) => {
+            let message = format!("cannot read {}: {}", path.display(), error);
+            return syn::Error::new(literal.span(), message).to_compile_error().into();
+        }
+    };
+
+    let resolver = sync::Arc::new(Tracker {
+        inner: bpfasm::FileResolver::new(path::Path::new(&path).parent()),
+        names: sync::Mutex::new(vec![path.clone()]),
+    });
+    let mut assembler = Assembler::default();
+    assembler.options.resolver = Some(resolver.clone());
+
+    match assembler.assemble(&source) {
+        Ok(insns) => {
+            // tells the compiler that the expansion depends on every file that was read
+            let names = resolver.names.lock().unwrap();
+            expand(&insns, quote! { #(const _: &[u8] = include_bytes!(#names);)* })
+        }
+        Err(error) => syn::Error::new(literal.span(), error.with_path(&path)).to_compile_error(),
+    }
+    .into()
+}
+
+fn expand(insns: &[Instruction], items: TokenStream) -> TokenStream {
+    let length = insns.len();
+    let insns = insns.iter().map(|Instruction { code, jt, jf, k }| {
+        quote! { ::bpfasm::Instruction { code: #code, jt: #jt, jf: #jf, k: #k } }
+    });
+
+    quote! {
+        {
+            #items
+            const INSTRUCTIONS: [::bpfasm::Instruction; #length] = [#(#insns),*];
+            INSTRUCTIONS
+        }
+    }
+}
+
+/// Assembler source rebuilt from the tokens of a macro invocation, laid out on the same lines and columns as in the
+/// invoking file, so that the assembler reports the same line numbers
+#[derive(Default)]
+struct Text {
+    source: String,
+    /// Byte range of each token within `source`
+    tokens: Vec<(std::ops::Range<usize>, Span)>,
+    line: usize,
+    column: usize,
+}
+
+impl Text {
+    fn push_stream(&mut self, stream: TokenStream) {
+        for tree in stream {
+            match tree {
+                TokenTree::Group(group) => {
+                    self.push(group.span_open());
+                    self.push_stream(group.stream());
+                    self.push(group.span_close());
+                }
+                tree => self.push(tree.span()),
+            }
+        }
+    }
+
+    fn push(&mut self, span: Span) {
+        let text = span.source_text().unwrap_or_default();
+        let (start, end) = (span.start(), span.end());
+        if start.line > self.line {
+            // lines are counted from 1, so the first token always starts a new line
+            self.source.extend(std::iter::repeat_n('\n', start.line - self.line.max(1)));
+            self.column = 0;
+        }
+        let padding = match start.column >= self.column {
+            true => start.column - self.column,
+            false => 1,
+        };
+        self.source.extend(std::iter::repeat_n(' ', padding));
+
+        self.tokens.push((self.source.len()..self.source.len() + text.len(), span));
+        self.source.push_str(&text);
+        (self.line, self.column) = (end.line.max(start.line), end.column);
+    }
+
+    /// Returns the span of the token that `error` points at
+    fn span(&self, error: &AssemblerError) -> Span {
+        let error = match error {
+            AssemblerError::Parse(error) => error,
+            AssemblerError::Macro { call, .. } => call,
+        };
+        if error.path().is_some() {
+            return Span::call_site();
+        }
+        let offset = match error.location {
+            pest::error::InputLocation::Pos(offset) => offset,
+            pest::error::InputLocation::Span((start, _)) => start,
+        };
+        self.tokens
+            .iter()
+            .find(|(range, _)| offset < range.end)
+            .or(self.tokens.last())
+            .map(|(_, span)| *span)
+            .unwrap_or_else(Span::call_site)
+    }
+}
+
+/// Resolves includes like `inner`, and remembers the name of every file it resolves
+struct Tracker {
+    inner: bpfasm::FileResolver,
+    names: sync::Mutex<Vec<String>>,
+}
+
+impl SourceResolver for Tracker {
+    fn resolve(&self, name: &str, parent: Option<&str>) -> io::Result<Source> {
+        let source = self.inner.resolve(name, parent)?;
+        self.names.lock().unwrap().push(source.name.clone());
+        Ok(source)
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+const EXPECTED: &str = "6,40 0 0 12,21 0 3 2048,48 0 0 23,21 0 1 6,6 0 0 4294967295,6 0 0 0";
+
+const FILTER: [bpfasm::Instruction; 6] = bpfasm_macros::bpf! {
+    ldh [12]            // load ethertype into accumulator
+    jne #0x0800, drop   // if accumulator != 0x0800: goto drop
+    ldb [23]            ; load ipproto into accumulator
+    jneq #0x06, drop    ; if accumulator != 0x06: goto drop
+    pass: ret #-1       /* pass */
+    drop: ret #0        /* drop */
+};
+
+static INCLUDED: &[bpfasm::Instruction] = &bpfasm_macros::bpf_file!("tests/filters/tcp.bpf");
+
+fn render(instructions: &[bpfasm::Instruction]) -> String {
+    format!("{},{}", instructions.len(), instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(","))
+}
+
+#[test]
+fn test_bpf() {
+    assert_eq!(render(&FILTER), EXPECTED);
+
+    let network = bpfasm_macros::bpf! {
+        jnet [26], 10.0.0.0/8, pass, drop
+        pass: ret #-1
+        drop: ret #0
+    };
+    assert_eq!(render(&network), "5,32 0 0 26,84 0 0 4278190080,21 0 1 167772160,6 0 0 4294967295,6 0 0 0");
+}
+
+#[test]
+fn test_bpf_file() {
+    assert_eq!(render(INCLUDED), EXPECTED);
+}
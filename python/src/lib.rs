@@ -0,0 +1,79 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Python interface to the assembler, built as the `bpfasm` extension module with maturin
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+pyo3::create_exception!(
+    bpfasm,
+    AssemblerError,
+    PyValueError,
+    "Raised when a source cannot be assembled. `line` and `column` count from 1, and `path` names the included file \
+     the error is in, or is None if it is in the source passed to `assemble`."
+);
+
+/// Assembles `source` for Linux socket filters and returns the program as a list of `(code, jt, jf, k)` tuples, or,
+/// if `bytes` is true, as a bytes object laid out like an array of the kernel's `struct sock_filter` in native byte
+/// order. `extensions` maps names to `k` values and replaces the default Linux extensions if it is given.
+#[pyfunction]
+#[pyo3(signature = (source, extensions=None, *, bytes=false))]
+fn assemble<'py>(
+    py: Python<'py>,
+    source: &str,
+    extensions: Option<HashMap<String, u32>>,
+    bytes: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let assembler = match extensions {
+        Some(extensions) => bpfasm::Assembler { extensions: Arc::new(extensions), ..Default::default() },
+        None => bpfasm::Assembler::default(),
+    };
+    let insns = assembler.assemble(source).map_err(|error| to_py_err(py, error))?;
+
+    if bytes {
+        let mut filter = Vec::with_capacity(insns.len() * 8);
+        for insn in insns.iter() {
+            filter.extend_from_slice(&insn.code.to_ne_bytes());
+            filter.extend_from_slice(&[insn.jt, insn.jf]);
+            filter.extend_from_slice(&insn.k.to_ne_bytes());
+        }
+        return Ok(PyBytes::new(py, &filter).into_any());
+    }
+
+    let tuples = insns.iter().map(|insn| (insn.code, insn.jt, insn.jf, insn.k)).collect::<Vec<_>>();
+    Ok(tuples.into_pyobject(py)?.into_any())
+}
+
+/// Converts `error` to an `AssemblerError` carrying the full diagnostic and its location
+fn to_py_err(py: Python, error: bpfasm::AssemblerError) -> PyErr {
+    let located = match &error {
+        bpfasm::AssemblerError::Parse(error) => error,
+        bpfasm::AssemblerError::Macro { call, .. } => call,
+    };
+    let (line, column) = match located.line_col {
+        pest::error::LineColLocation::Pos(position) => position,
+        pest::error::LineColLocation::Span(start, _) => start,
+    };
+    let path = located.path().map(str::to_string);
+
+    let exception = AssemblerError::new_err(error.to_string());
+    let value = exception.value(py);
+    let attributes = value.setattr("line", line).and(value.setattr("column", column)).and(value.setattr("path", path));
+    match attributes {
+        Ok(()) => exception,
+        Err(error) => error,
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "bpfasm")]
+pub fn bpfasm_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("AssemblerError", m.py().get_type::<AssemblerError>())?;
+    m.add_function(wrap_pyfunction!(assemble, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,65 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::ffi;
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// Runs `code` with the `bpfasm` module imported
+fn run(code: &ffi::CStr) {
+    Python::attach(|py| {
+        let module = pyo3::wrap_pymodule!(bpfasm_python::bpfasm_python)(py);
+        let globals = PyDict::new(py);
+        globals.set_item("bpfasm", module).unwrap();
+        if let Err(error) = py.run(code, Some(&globals), None) {
+            error.print(py);
+            panic!("{}", error);
+        }
+    });
+}
+
+#[test]
+fn test_assemble() {
+    run(c"
+program = bpfasm.assemble('ldh [12]\\njne #0x800, drop\\nret #-1\\ndrop: ret #0\\n')
+assert program == [(0x28, 0, 0, 12), (0x15, 0, 1, 0x800), (0x06, 0, 0, 0xffffffff), (0x06, 0, 0, 0)], program
+
+import struct
+raw = bpfasm.assemble('ld proto\\nret a\\n', bytes=True)
+assert struct.unpack('=HBBIHBBI', raw) == (0x20, 0, 0, 0xfffff000, 0x16, 0, 0, 0), raw
+
+program = bpfasm.assemble('ldh ether_type\\nret a\\n', {'ether_type': 12})
+assert program == [(0x28, 0, 0, 12), (0x16, 0, 0, 0)], program
+");
+}
+
+#[test]
+fn test_error() {
+    run(c"
+try:
+    bpfasm.assemble('ldh [12]\\njne #0x800, nowhere\\nret #0\\n')
+except bpfasm.AssemblerError as error:
+    assert 'undeclared label \"nowhere\"' in str(error), str(error)
+    assert (error.line, error.column, error.path) == (2, 13, None), (error.line, error.column, error.path)
+    assert isinstance(error, ValueError)
+else:
+    assert False, 'no exception was raised'
+
+try:
+    bpfasm.assemble('ld proto\\nret a\\n', {})
+except bpfasm.AssemblerError as error:
+    assert error.line == 1, error.line
+else:
+    assert False, 'no exception was raised'
+
+for extensions in [None, {'proto': 0xfffff000}]:
+    try:
+        bpfasm.assemble('ld #0\\nldh proto\\nret a\\n', extensions)
+    except bpfasm.AssemblerError as error:
+        assert 'can only be loaded by ld' in str(error), str(error)
+        assert error.line == 2, error.line
+    else:
+        assert False, 'no exception was raised'
+");
+}
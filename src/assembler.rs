@@ -2,66 +2,142 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use std::collections;
+use std::net;
+use std::sync;
 
 use pest::iterators::*;
-use pest::*;
 
+use crate::extensions::{ExtensionResolver, Width};
+use crate::opcodes::*;
+use crate::preprocessor::*;
 use crate::*;
 
 #[derive(Debug, thiserror::Error)]
 pub enum AssemblerError {
     #[error(transparent)]
     Parse(#[from] pest::error::Error<Rule>),
+    #[error("{source}\n{call}\n{definition}")]
+    Macro {
+        source: Box<AssemblerError>,
+        call: Box<pest::error::Error<Rule>>,
+        definition: Box<pest::error::Error<Rule>>,
+    },
 }
 
-const BPF_LD: u16 = 0x00;
-const BPF_LDX: u16 = 0x01;
-const BPF_ST: u16 = 0x02;
-const BPF_STX: u16 = 0x03;
-const BPF_ALU: u16 = 0x04;
-const BPF_JMP: u16 = 0x05;
-const BPF_RET: u16 = 0x06;
-const BPF_MISC: u16 = 0x07;
-
-const BPF_W: u16 = 0x00;
-const BPF_H: u16 = 0x08;
-const BPF_B: u16 = 0x10;
-
-const BPF_IMM: u16 = 0x00;
-const BPF_ABS: u16 = 0x20;
-const BPF_IND: u16 = 0x40;
-const BPF_MEM: u16 = 0x60;
-const BPF_LEN: u16 = 0x80;
-const BPF_MSH: u16 = 0xa0;
-
-const BPF_ADD: u16 = 0x00;
-const BPF_SUB: u16 = 0x10;
-const BPF_MUL: u16 = 0x20;
-const BPF_DIV: u16 = 0x30;
-const BPF_OR: u16 = 0x40;
-const BPF_AND: u16 = 0x50;
-const BPF_LSH: u16 = 0x60;
-const BPF_RSH: u16 = 0x70;
-const BPF_NEG: u16 = 0x80;
-const BPF_MOD: u16 = 0x90;
-const BPF_XOR: u16 = 0xa0;
-
-const BPF_JA: u16 = 0x00;
-const BPF_JEQ: u16 = 0x10;
-const BPF_JGT: u16 = 0x20;
-const BPF_JGE: u16 = 0x30;
-const BPF_JSET: u16 = 0x40;
-
-const BPF_K: u16 = 0x00;
-const BPF_X: u16 = 0x08;
-const BPF_A: u16 = 0x10;
-
-const BPF_TAX: u16 = 0x00;
-const BPF_COP: u16 = 0x20;
-const BPF_COPX: u16 = 0x40;
-const BPF_TXA: u16 = 0x80;
-
-fn pair_to_u32(pair: Pair<Rule>) -> Result<u32, AssemblerError> {
+impl AssemblerError {
+    /// Attributes an error that originated in an unnamed source to the file at `path`.
+    pub fn with_path(self, path: &str) -> Self {
+        match self {
+            AssemblerError::Parse(error) if error.path().is_none() => AssemblerError::Parse(error.with_path(path)),
+            AssemblerError::Macro { source, call, definition } if call.path().is_none() => {
+                AssemblerError::Macro { source, call: Box::new(call.with_path(path)), definition }
+            }
+            error => error,
+        }
+    }
+}
+
+/// Options that control how `assemble_with_options` processes a source
+#[derive(Default, Clone)]
+pub struct Options {
+    /// Resolves the files named by `.include` directives
+    pub resolver: Option<sync::Arc<dyn SourceResolver + Send + Sync>>,
+    /// Names and values that `.if` and `.ifdef` conditions are evaluated against
+    pub symbols: collections::HashMap<String, u32>,
+    /// Implementation whose restrictions the program is checked against, or `None` to skip those checks
+    pub dialect: Option<Dialect>,
+    /// Kind of program being assembled; seccomp programs may only use the instructions seccomp accepts
+    pub profile: Profile,
+    /// Size limits and how they are enforced
+    pub limits: Limits,
+}
+
+/// Limits placed on an assembled program
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Limits {
+    /// Longest program that may be produced, which defaults to the kernel's `BPF_MAXINSNS`
+    pub max_instructions: usize,
+    /// Whether a conditional jump whose label is more than 255 instructions away is rewritten to jump through a
+    /// pair of `ja` instructions, rather than reported as an error
+    pub relaxation: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { max_instructions: 4096, relaxation: false }
+    }
+}
+
+/// An assembler configured once and then used to assemble any number of sources
+///
+/// ```
+/// let assembler = bpfasm::Assembler::default();
+/// let drop = assembler.assemble("ret #0").unwrap();
+/// let accept = assembler.assemble("ret #-1").unwrap();
+/// assert_eq!((drop[0].k, accept[0].k), (0, u32::MAX));
+/// ```
+#[derive(Clone)]
+pub struct Assembler {
+    /// Resolves extension operands such as `ld proto`
+    pub extensions: sync::Arc<dyn ExtensionResolver + Send + Sync>,
+    /// Options used for every source
+    pub options: Options,
+}
+
+impl Default for Assembler {
+    /// Creates an assembler for Linux socket filters with the default `Options`, which like `assemble` check the
+    /// program against no dialect; set `options.dialect` to enable those checks
+    fn default() -> Self {
+        Assembler { extensions: sync::Arc::new(extensions::linux_table()), options: Options::default() }
+    }
+}
+
+impl Assembler {
+    /// Assembles `source` like `assemble_with_options`
+    pub fn assemble<S: AsRef<str>>(&self, source: S) -> Result<Vec<Instruction>, AssemblerError> {
+        assemble_with_options(source, &self.extensions, &self.options)
+    }
+
+    /// Assembles `source` like `assemble_with_debug_info`
+    pub fn assemble_with_debug_info<S: AsRef<str>>(
+        &self,
+        source: S,
+    ) -> Result<(Vec<Instruction>, DebugInfo), AssemblerError> {
+        assemble_with_debug_info(source, &self.extensions, &self.options)
+    }
+
+    /// Assembles `source` and returns a listing of the result, like `listing`
+    pub fn listing<S: AsRef<str>>(&self, source: S) -> Result<String, AssemblerError> {
+        listing(source, &self.extensions, &self.options)
+    }
+}
+
+/// The place in a source file that an instruction was assembled from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Name of the included `Source` containing the statement, or `None` for the source passed to the assembler
+    pub path: Option<String>,
+    /// Byte range of the statement within that source
+    pub span: std::ops::Range<usize>,
+    /// Line of the start of the statement, counting from 1
+    pub line: usize,
+    /// Column of the start of the statement, counting from 1
+    pub column: usize,
+}
+
+/// Debugging information returned by `assemble_with_debug_info`
+#[derive(Debug, Clone, Default)]
+pub struct DebugInfo {
+    /// Source location of every instruction, indexed by program counter. Instructions produced by a macro
+    /// expansion are located at the macro call, and all instructions produced by a pseudo-instruction share its
+    /// location.
+    pub locations: Vec<SourceLocation>,
+    /// Program counter of every label. Labels declared inside a macro expansion are named `label@N`, where `N`
+    /// numbers the expansion.
+    pub labels: collections::HashMap<String, u32>,
+}
+
+pub(crate) fn pair_to_u32(pair: Pair<Rule>) -> Result<u32, AssemblerError> {
     let err = || {
         pest::error::Error::new_from_span(
             pest::error::ErrorVariant::CustomError { message: format!("invalid integer literal {:?}", pair.as_str()) },
@@ -72,8 +148,15 @@ fn pair_to_u32(pair: Pair<Rule>) -> Result<u32, AssemblerError> {
     match pair.as_rule() {
         Rule::Binary => u32::from_str_radix(&pair.as_str()[2..], 2).map_err(|_| err()),
         Rule::Octal => u32::from_str_radix(&pair.as_str()[1..], 8).map_err(|_| err()),
-        Rule::Decimal => i32::from_str_radix(pair.as_str(), 10).map(|i| i as u32).map_err(|_| err()),
+        Rule::Decimal => pair.as_str().parse::<i32>().map(|i| i as u32).map_err(|_| err()),
         Rule::Hexadecimal => u32::from_str_radix(&pair.as_str()[2..], 16).map_err(|_| err()),
+        Rule::IPv4Address => pair.as_str().parse::<net::Ipv4Addr>().map(u32::from).map_err(|_| {
+            pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError { message: format!("invalid IPv4 address {:?}", pair.as_str()) },
+                pair.as_span(),
+            )
+            .into()
+        }),
         _ => Err(err()),
     }
 }
@@ -90,11 +173,34 @@ fn insn(code: u16, mut operands: Pairs<Rule>) -> Result<Instruction, AssemblerEr
 fn xinsn(
     code: u16,
     mut operands: Pairs<Rule>,
-    extensions: &collections::HashMap<String, u32>,
+    extensions: &dyn ExtensionResolver,
 ) -> Result<Instruction, AssemblerError> {
-    let k = match operands.next() {
-        Some(extension) => match extensions.get(extension.as_str()) {
-            Some(i) => Ok(*i),
+    let (code, k) = match operands.next() {
+        Some(extension) => match extensions.resolve(extension.as_str()) {
+            Some(resolved) if code == BPF_MISC | BPF_COP && !resolved.cop => Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!("extension {:?} is not a coprocessor function", extension.as_str()),
+                },
+                extension.as_span(),
+            )),
+            Some(resolved) if class(code) == BPF_LD && !resolved.widths.contains(&Width::of(code)) => {
+                Err(pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError {
+                        message: match resolved.widths.is_empty() {
+                            true => format!("extension {:?} cannot be loaded", extension.as_str()),
+                            false => format!(
+                                "extension {:?} cannot be loaded by {}; it can only be loaded by {}",
+                                extension.as_str(),
+                                Width::of(code).mnemonic(),
+                                resolved.widths.iter().map(|width| width.mnemonic()).collect::<Vec<_>>().join(" or ")
+                            ),
+                        },
+                    },
+                    extension.as_span(),
+                ))
+            }
+            Some(resolved) if class(code) == BPF_LD => Ok((resolved.code.unwrap_or(code), resolved.offset)),
+            Some(resolved) => Ok((code, resolved.offset)),
             None => Err(pest::error::Error::new_from_span(
                 pest::error::ErrorVariant::CustomError {
                     message: format!("invalid extension {:?}", extension.as_str()),
@@ -102,92 +208,144 @@ fn xinsn(
                 extension.as_span(),
             )),
         },
-        None => Ok(0),
+        None => Ok((code, 0)),
     }?;
 
     Ok(Instruction { code, jt: 0, jf: 0, k })
 }
 
-fn jainsn(
-    pc: usize,
-    code: u16,
-    mut operands: Pairs<Rule>,
-    labels: &collections::HashMap<String, u32>,
-) -> Result<Instruction, AssemblerError> {
-    let k = match operands.next() {
-        Some(label) => match labels.get(label.as_str()) {
-            Some(i) if *i as usize > pc => Ok((*i as usize - pc - 1) as u32),
+struct Labels<'a> {
+    scope: &'a Scope<'a>,
+    pcs: &'a collections::HashMap<String, u32>,
+    relaxation: bool,
+}
+
+impl Labels<'_> {
+    fn target(&self, pc: usize, label: Pair<Rule>) -> Result<usize, AssemblerError> {
+        match self.pcs.get(&self.scope.resolve(label.as_span())) {
+            Some(i) if *i as usize > pc => Ok(*i as usize - pc - 1),
             Some(_) => Err(pest::error::Error::new_from_span(
                 pest::error::ErrorVariant::CustomError { message: format!("unreachable label {:?}", label.as_str()) },
                 label.as_span(),
-            )),
+            ))?,
             None => Err(pest::error::Error::new_from_span(
                 pest::error::ErrorVariant::CustomError { message: format!("undeclared label {:?}", label.as_str()) },
                 label.as_span(),
-            )),
-        },
-        None => Ok(0),
+            ))?,
+        }
+    }
+}
+
+struct Variables<'a> {
+    scope: &'a Scope<'a>,
+    /// Slot of every variable, and the position of the statement that declares it
+    slots: &'a collections::HashMap<String, (u32, usize)>,
+    /// Position of the statement being assembled
+    position: usize,
+}
+
+impl Variables<'_> {
+    fn get(&self, name: pest::Span) -> Option<(u32, usize)> {
+        self.slots.get(&self.scope.resolve(name)).copied()
+    }
+
+    /// Whether the operand of an extension load names a variable
+    fn declared(&self, operands: &Pairs<Rule>) -> bool {
+        self.get(operands.peek().unwrap().as_span()).is_some()
+    }
+
+    fn width(&self, mut operands: Pairs<Rule>) -> pest::error::Error<Rule> {
+        let name = operands.next().unwrap();
+        pest::error::Error::new_from_span(
+            pest::error::ErrorVariant::CustomError {
+                message: format!("variable {:?} is a word and can only be loaded by ld or ldx", name.as_str()),
+            },
+            name.as_span(),
+        )
+    }
+}
+
+fn vinsn(code: u16, mut operands: Pairs<Rule>, variables: &Variables) -> Result<Instruction, AssemblerError> {
+    let name = operands.next().unwrap();
+    let k = match variables.get(name.as_span()) {
+        Some((slot, position)) if position < variables.position => Ok(slot),
+        Some(_) => Err(pest::error::Error::new_from_span(
+            pest::error::ErrorVariant::CustomError {
+                message: format!("variable {:?} used before declaration", name.as_str()),
+            },
+            name.as_span(),
+        )),
+        None => Err(pest::error::Error::new_from_span(
+            pest::error::ErrorVariant::CustomError { message: format!("undeclared variable {:?}", name.as_str()) },
+            name.as_span(),
+        )),
     }?;
 
     Ok(Instruction { code, jt: 0, jf: 0, k })
 }
 
-fn jinsn(
-    pc: usize,
-    code: u16,
-    immediate: bool,
-    mut operands: Pairs<Rule>,
-    labels: &collections::HashMap<String, u32>,
-) -> Result<Instruction, AssemblerError> {
-    let k = if immediate {
-        match operands.next() {
-            Some(pair) => pair_to_u32(pair)?,
-            None => 0,
-        }
-    } else {
-        0
+fn jainsn(pc: usize, code: u16, mut operands: Pairs<Rule>, labels: &Labels) -> Result<Instruction, AssemblerError> {
+    let k = match operands.next() {
+        Some(label) => labels.target(pc, label)? as u32,
+        None => 0,
     };
 
-    let jt = match operands.next() {
-        Some(label) => match labels.get(label.as_str()) {
-            Some(i) if *i as usize > pc => Ok((*i as usize - pc - 1) as u8),
-            Some(_) => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("unreachable label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-            None => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("undeclared label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-        },
-        None => Ok(0),
-    }?;
+    Ok(Instruction { code, jt: 0, jf: 0, k })
+}
 
-    let jf = match operands.next() {
-        Some(label) => match labels.get(label.as_str()) {
-            Some(i) if *i as usize > pc => Ok((*i as usize - pc - 1) as u8),
-            Some(_) => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("unreachable label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-            None => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("undeclared label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-        },
-        None => Ok(0),
-    }?;
+/// A conditional jump whose targets have not yet been resolved; a missing target falls through
+struct Jump<'i> {
+    insn: Instruction,
+    jt: Option<Pair<'i, Rule>>,
+    jf: Option<Pair<'i, Rule>>,
+}
+
+impl Jump<'_> {
+    /// Encodes the jump at `pc`. A relaxed jump is followed by a `ja` to each of its targets, so that they can be any
+    /// distance away. Returns `None` if a target is out of reach of an unrelaxed jump and relaxation is enabled.
+    fn encode(self, pc: usize, labels: &Labels, relaxed: bool) -> Result<Option<Vec<Instruction>>, AssemblerError> {
+        if relaxed {
+            let jt = match self.jt {
+                Some(label) => labels.target(pc + 1, label)? as u32,
+                None => 1,
+            };
+            let jf = match self.jf {
+                Some(label) => labels.target(pc + 2, label)? as u32,
+                None => 0,
+            };
+            return Ok(Some(vec![
+                Instruction { jt: 0, jf: 1, ..self.insn },
+                Instruction { code: BPF_JMP | BPF_JA, jt: 0, jf: 0, k: jt },
+                Instruction { code: BPF_JMP | BPF_JA, jt: 0, jf: 0, k: jf },
+            ]));
+        }
 
-    Ok(Instruction { code, jt, jf, k })
+        let mut offsets = [0u8; 2];
+        for (offset, label) in offsets.iter_mut().zip([self.jt, self.jf]) {
+            if let Some(label) = label {
+                match labels.target(pc, label.clone())? {
+                    target if target <= u8::MAX as usize => *offset = target as u8,
+                    _ if labels.relaxation => return Ok(None),
+                    target => Err(pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: format!(
+                                "label {:?} is {} instructions away, but a conditional jump can skip at most {}",
+                                label.as_str(),
+                                target,
+                                u8::MAX
+                            ),
+                        },
+                        label.as_span(),
+                    ))?,
+                }
+            }
+        }
+
+        Ok(Some(vec![Instruction { jt: offsets[0], jf: offsets[1], ..self.insn }]))
+    }
 }
 
-fn jtinsn(
-    pc: usize,
-    code: u16,
-    immediate: bool,
-    mut operands: Pairs<Rule>,
-    labels: &collections::HashMap<String, u32>,
-) -> Result<Instruction, AssemblerError> {
+fn jinsn(code: u16, immediate: bool, mut operands: Pairs<Rule>) -> Result<Jump, AssemblerError> {
     let k = if immediate {
         match operands.next() {
             Some(pair) => pair_to_u32(pair)?,
@@ -197,94 +355,329 @@ fn jtinsn(
         0
     };
 
-    let jt = match operands.next() {
-        Some(label) => match labels.get(label.as_str()) {
-            Some(i) if *i as usize > pc => Ok((*i as usize - pc - 1) as u8),
-            Some(_) => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("unreachable label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-            None => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("undeclared label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-        },
-        None => Ok(0),
-    }?;
+    Ok(Jump { insn: Instruction { code, jt: 0, jf: 0, k }, jt: operands.next(), jf: operands.next() })
+}
 
-    Ok(Instruction { code, jt, jf: 0, k })
+fn jfinsn(code: u16, immediate: bool, operands: Pairs<Rule>) -> Result<Jump, AssemblerError> {
+    let jump = jinsn(code, immediate, operands)?;
+
+    Ok(Jump { jt: None, jf: jump.jt, ..jump })
 }
 
-fn jfinsn(
-    pc: usize,
-    code: u16,
-    immediate: bool,
-    mut operands: Pairs<Rule>,
-    labels: &collections::HashMap<String, u32>,
-) -> Result<Instruction, AssemblerError> {
-    let k = if immediate {
-        match operands.next() {
-            Some(pair) => pair_to_u32(pair)?,
-            None => 0,
-        }
-    } else {
-        0
+fn jnetinsns(mut operands: Pairs<Rule>) -> Result<([Instruction; 2], Jump), AssemblerError> {
+    let offset = operands.next().unwrap();
+    let load = match offset.as_rule() {
+        Rule::PacketOffset => insn(BPF_LD | BPF_W | BPF_ABS, offset.into_inner())?,
+        Rule::IndirectPacketOffset => insn(BPF_LD | BPF_W | BPF_IND, offset.into_inner())?,
+        _ => unreachable!(),
     };
 
-    let jf = match operands.next() {
-        Some(label) => match labels.get(label.as_str()) {
-            Some(i) if *i as usize > pc => Ok((*i as usize - pc - 1) as u8),
-            Some(_) => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("unreachable label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-            None => Err(pest::error::Error::new_from_span(
-                pest::error::ErrorVariant::CustomError { message: format!("undeclared label {:?}", label.as_str()) },
-                label.as_span(),
-            )),
-        },
-        None => Ok(0),
+    let network = operands.next().unwrap();
+    let network_span = network.as_span();
+    let mut network_pairs = network.into_inner();
+    let address = pair_to_u32(network_pairs.next().unwrap())?;
+    let prefix_length = network_pairs.next().unwrap();
+    let mask = match prefix_length.as_str().parse::<u32>() {
+        Ok(n) if n <= 32 => Ok(u32::MAX.checked_shl(32 - n).unwrap_or(0)),
+        _ => Err(pest::error::Error::new_from_span(
+            pest::error::ErrorVariant::CustomError {
+                message: format!("invalid prefix length {:?}", prefix_length.as_str()),
+            },
+            prefix_length.as_span(),
+        )),
     }?;
+    if address & !mask != 0 {
+        Err(pest::error::Error::new_from_span(
+            pest::error::ErrorVariant::CustomError {
+                message: format!(
+                    "host bits set in network {:?} (did you mean {}/{}?)",
+                    network_span.as_str(),
+                    net::Ipv4Addr::from(address & mask),
+                    prefix_length.as_str()
+                ),
+            },
+            network_span,
+        ))?;
+    }
+
+    let mut jump = jinsn(BPF_JMP | BPF_JEQ | BPF_K, false, operands)?;
+    jump.insn.k = address;
 
-    Ok(Instruction { code, jt: 0, jf, k })
+    Ok(([load, Instruction { code: BPF_ALU | BPF_AND | BPF_K, jt: 0, jf: 0, k: mask }], jump))
 }
 
-pub fn assemble<S: AsRef<str>>(
+/// Assembles `source` with the default `Options`, which check the program against no dialect, like
+/// `Assembler::default`
+pub fn assemble<S: AsRef<str>, E: ExtensionResolver + ?Sized>(
     source: S,
-    extensions: &collections::HashMap<String, u32>,
+    extensions: &E,
 ) -> Result<Vec<Instruction>, AssemblerError> {
+    assemble_with_options(source, extensions, &Options::default())
+}
+
+pub fn assemble_with_options<S: AsRef<str>, E: ExtensionResolver + ?Sized>(
+    source: S,
+    extensions: &E,
+    options: &Options,
+) -> Result<Vec<Instruction>, AssemblerError> {
+    assemble_with_debug_info(source, extensions, options).map(|(insns, _)| insns)
+}
+
+/// Assembles `source` like `assemble_with_options`, and also returns where each instruction came from along with
+/// the resolved label table
+pub fn assemble_with_debug_info<S: AsRef<str>, E: ExtensionResolver + ?Sized>(
+    source: S,
+    extensions: &E,
+    options: &Options,
+) -> Result<(Vec<Instruction>, DebugInfo), AssemblerError> {
+    let extensions: &dyn ExtensionResolver = &extensions;
+    assemble_program(source.as_ref(), extensions, options).map(|program| (program.instructions, program.debug_info))
+}
+
+/// Everything produced by assembling a source
+pub(crate) struct Program {
+    pub(crate) instructions: Vec<Instruction>,
+    pub(crate) debug_info: DebugInfo,
+    /// Text of every included source, by name
+    pub(crate) sources: collections::HashMap<String, String>,
+}
+
+pub(crate) fn assemble_program(
+    source: &str,
+    extensions: &dyn ExtensionResolver,
+    options: &Options,
+) -> Result<Program, AssemblerError> {
+    // statements whose conditional jump is followed by trampolines, counted in the order they are visited
+    let mut relaxed = collections::HashSet::default();
+    let mut includes = Includes::default();
+    let program = loop {
+        let (program, far) = assemble_once(source, extensions, options, &relaxed, &mut includes)?;
+        if far.is_empty() {
+            break program;
+        }
+        relaxed.extend(far);
+    };
+
+    validate(&program, source, options)?;
+
+    Ok(program)
+}
+
+/// Checks an assembled program against the limits, dialect and profile in `options`
+fn validate(program: &Program, source: &str, options: &Options) -> Result<(), AssemblerError> {
+    let error = |pc: usize, message: String| -> AssemblerError {
+        let location = &program.debug_info.locations[pc];
+        let text = match &location.path {
+            Some(path) => program.sources[path].as_str(),
+            None => source,
+        };
+        let span = pest::Span::new(text, location.span.start, location.span.end).unwrap();
+        let error = AssemblerError::from(pest::error::Error::new_from_span(
+            pest::error::ErrorVariant::CustomError { message },
+            span,
+        ));
+        match &location.path {
+            Some(path) => error.with_path(path),
+            None => error,
+        }
+    };
+
+    let insns = &program.instructions;
+    if insns.len() > options.limits.max_instructions {
+        let message = format!(
+            "program is {} instructions long, but at most {} are allowed",
+            insns.len(),
+            options.limits.max_instructions
+        );
+        return Err(error(options.limits.max_instructions, message));
+    }
+
+    if let Some(dialect) = options.dialect {
+        for (pc, insn) in insns.iter().enumerate() {
+            let message = match class(insn.code) {
+                BPF_MISC if matches!(miscop(insn.code), BPF_COP | BPF_COPX) && dialect != Dialect::NetBSD => {
+                    format!("the {} dialect has no coprocessor", dialect)
+                }
+                BPF_LD if mode(insn.code) == BPF_RND && dialect != Dialect::OpenBSD => {
+                    format!("the {} dialect has no random number load", dialect)
+                }
+                BPF_LD if mode(insn.code) == BPF_ABS && insn.k >= SKF_LL_OFF && dialect != Dialect::Linux => {
+                    format!("the {} dialect has no ancillary data area", dialect)
+                }
+                BPF_RET if rval(insn.code) == BPF_X => format!("the {} dialect cannot return x", dialect),
+                BPF_ALU if src(insn.code) == BPF_K && matches!(op(insn.code), BPF_DIV | BPF_MOD) && insn.k == 0 => {
+                    "division by zero".to_string()
+                }
+                BPF_ALU if src(insn.code) == BPF_K && matches!(op(insn.code), BPF_LSH | BPF_RSH) && insn.k >= 32 => {
+                    match dialect {
+                        Dialect::Linux => "shift by 32 or more bits".to_string(),
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+            return Err(error(pc, message));
+        }
+        match insns.last() {
+            Some(insn) if class(insn.code) == BPF_RET => {}
+            Some(_) => return Err(error(insns.len() - 1, "program must end with a return instruction".to_string())),
+            None => Err(pest::error::Error::new_from_pos(
+                pest::error::ErrorVariant::<Rule>::CustomError { message: "program is empty".to_string() },
+                pest::Position::new(source, source.len()).unwrap(),
+            ))?,
+        }
+    }
+
+    if options.profile == Profile::Seccomp {
+        for (pc, insn) in insns.iter().enumerate() {
+            let allowed = match class(insn.code) {
+                BPF_LD | BPF_LDX if mode(insn.code) == BPF_ABS => {
+                    size(insn.code) == BPF_W && insn.k < SECCOMP_DATA_SIZE && insn.k % 4 == 0
+                }
+                BPF_LD | BPF_LDX => matches!(mode(insn.code), BPF_IMM | BPF_MEM | BPF_LEN),
+                BPF_ALU => op(insn.code) != BPF_MOD,
+                BPF_MISC => matches!(miscop(insn.code), BPF_TAX | BPF_TXA),
+                BPF_RET => rval(insn.code) != BPF_X,
+                _ => true,
+            };
+            if !allowed {
+                let message = format!("\"{}\" is not allowed in a seccomp filter", insn);
+                return Err(error(pc, message));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn assemble_once(
+    source: &str,
+    extensions: &dyn ExtensionResolver,
+    options: &Options,
+    relaxed: &collections::HashSet<usize>,
+    includes: &mut Includes,
+) -> Result<(Program, collections::HashSet<usize>), AssemblerError> {
     let mut labels = collections::HashMap::default();
     let mut insns: Vec<Instruction> = Vec::default();
 
-    let pairs = crate::Parser::parse(Rule::Program, source.as_ref())?;
+    let mut variables = collections::HashMap::default();
+    let mut declarations = Vec::default();
+    let mut reserved = [false; BPF_MEMWORDS];
+    // the position of the last statement of each macro expansion
+    let mut ends = collections::HashMap::<usize, usize>::default();
 
     let mut pc = 0;
+    let mut statement = 0;
+    let mut position = 0;
 
-    for pair in pairs.clone() {
+    Preprocessor::new(options, includes).run(source, &mut |pair, scope| {
         let rule = pair.as_rule();
         let span = pair.as_span();
+        position += 1;
+        for expansion in scope.expansions() {
+            ends.insert(expansion, position);
+        }
         match rule {
             Rule::Label => {
-                let name = pair.into_inner().next().unwrap().as_str();
-                if let Some(_) = labels.insert(name.into(), pc as u32) {
+                let name = pair.clone().into_inner().next().unwrap();
+                if labels.insert(scope.resolve(name.as_span()), pc as u32).is_some() {
                     Err(pest::error::Error::new_from_span(
-                        pest::error::ErrorVariant::CustomError { message: format!("redeclared label {:?}", name) },
+                        pest::error::ErrorVariant::CustomError {
+                            message: format!("redeclared label {:?}", name.as_str()),
+                        },
                         span,
                     ))?;
                 }
-                continue;
+                return Ok(());
+            }
+            Rule::Var => {
+                let name = pair.into_inner().next().unwrap();
+                if matches!(name.as_str(), "len" | "pktlen") || extensions.resolve(name.as_str()).is_some() {
+                    Err(pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: format!("variable {:?} shadows a keyword or extension", name.as_str()),
+                        },
+                        name.as_span(),
+                    ))?;
+                }
+                if variables.insert(scope.resolve(name.as_span()), (0, position)).is_some() {
+                    Err(pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: format!("redeclared variable {:?}", name.as_str()),
+                        },
+                        name.as_span(),
+                    ))?;
+                }
+                // the error is built now because the span cannot outlive this pass
+                declarations.push((
+                    scope.resolve(name.as_span()),
+                    position,
+                    scope.owner(name.as_span()),
+                    pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: format!(
+                                "no free scratch memory slot for variable {:?}; M[0] to M[{}] are all in use",
+                                name.as_str(),
+                                BPF_MEMWORDS - 1
+                            ),
+                        },
+                        name.as_span(),
+                    ),
+                ));
+                return Ok(());
+            }
+            Rule::JNET => {
+                pc += 3;
             }
             _ => {
                 pc += 1;
             }
         }
+        if relaxed.contains(&statement) {
+            pc += 2;
+        }
+        statement += 1;
+
+        // slots used through explicit M[k] operands are never handed out to variables
+        for operand in pair.into_inner().flatten().filter(|pair| pair.as_rule() == Rule::MemoryAddress) {
+            if let Ok(k) = pair_to_u32(operand.into_inner().next().unwrap()) {
+                if let Some(slot) = reserved.get_mut(k as usize) {
+                    *slot = true;
+                }
+            }
+        }
+
+        Ok(())
+    })?;
+
+    // a variable that is local to a macro expansion only needs its slot until the expansion ends, because jumps only
+    // go forward and nothing after the expansion can refer to it
+    let mut busy = reserved.map(|reserved| if reserved { usize::MAX } else { 0 });
+    for (name, position, owner, error) in declarations {
+        match (0..BPF_MEMWORDS).find(|k| busy[*k] < position) {
+            Some(slot) => {
+                busy[slot] = owner.map_or(usize::MAX, |expansion| ends[&expansion]);
+                variables.insert(name, (slot as u32, position));
+            }
+            None => Err(error)?,
+        };
     }
 
-    pc = 0;
+    let mut locations = Vec::default();
+    let mut far = collections::HashSet::default();
+    let mut statement = 0;
+    let mut position = 0;
 
-    for pair in pairs {
+    let mut preprocessor = Preprocessor::new(options, includes);
+    preprocessor.run(source, &mut |pair, scope| {
+        let pc = insns.len();
+        let location = scope.locate(pair.as_span());
+        position += 1;
+        let labels = Labels { scope, pcs: &labels, relaxation: options.limits.relaxation };
+        let variables = Variables { scope, slots: &variables, position };
         let rule = pair.as_rule();
         let mut inner_pairs = pair.into_inner();
+        let mut jump = None;
         match rule {
             Rule::LD => {
                 let expression = inner_pairs.next().unwrap();
@@ -296,6 +689,7 @@ pub fn assemble<S: AsRef<str>>(
                     Rule::MemoryAddress => insn(BPF_LD | BPF_MEM, operands)?,
                     Rule::Immediate => insn(BPF_LD | BPF_IMM, operands)?,
                     Rule::Length => insn(BPF_LD | BPF_W | BPF_LEN, operands)?,
+                    Rule::Extension if variables.declared(&operands) => vinsn(BPF_LD | BPF_MEM, operands, &variables)?,
                     Rule::Extension => xinsn(BPF_LD | BPF_W | BPF_ABS, operands, extensions)?,
                     _ => unreachable!(),
                 });
@@ -316,6 +710,7 @@ pub fn assemble<S: AsRef<str>>(
                 insns.push(match rule {
                     Rule::PacketOffset => insn(BPF_LD | BPF_H | BPF_ABS, operands)?,
                     Rule::IndirectPacketOffset => insn(BPF_LD | BPF_H | BPF_IND, operands)?,
+                    Rule::Extension if variables.declared(&operands) => Err(variables.width(operands))?,
                     Rule::Extension => xinsn(BPF_LD | BPF_H | BPF_ABS, operands, extensions)?,
                     _ => unreachable!(),
                 });
@@ -327,6 +722,7 @@ pub fn assemble<S: AsRef<str>>(
                 insns.push(match rule {
                     Rule::PacketOffset => insn(BPF_LD | BPF_B | BPF_ABS, operands)?,
                     Rule::IndirectPacketOffset => insn(BPF_LD | BPF_B | BPF_IND, operands)?,
+                    Rule::Extension if variables.declared(&operands) => Err(variables.width(operands))?,
                     Rule::Extension => xinsn(BPF_LD | BPF_B | BPF_ABS, operands, extensions)?,
                     _ => unreachable!(),
                 });
@@ -340,6 +736,7 @@ pub fn assemble<S: AsRef<str>>(
                     Rule::PacketOffsetMSH => insn(BPF_LDX | BPF_MSH | BPF_B, operands)?,
                     Rule::Immediate => insn(BPF_LDX | BPF_IMM, operands)?,
                     Rule::Length => insn(BPF_LDX | BPF_W | BPF_LEN, operands)?,
+                    Rule::Variable => vinsn(BPF_LDX | BPF_MEM, operands, &variables)?,
                     _ => unreachable!(),
                 });
             }
@@ -367,6 +764,7 @@ pub fn assemble<S: AsRef<str>>(
                 let operands = expression.into_inner();
                 insns.push(match rule {
                     Rule::MemoryAddress => insn(BPF_ST, operands)?,
+                    Rule::Variable => vinsn(BPF_ST, operands, &variables)?,
                     _ => unreachable!(),
                 });
             }
@@ -376,6 +774,7 @@ pub fn assemble<S: AsRef<str>>(
                 let operands = expression.into_inner();
                 insns.push(match rule {
                     Rule::MemoryAddress => insn(BPF_STX, operands)?,
+                    Rule::Variable => vinsn(BPF_STX, operands, &variables)?,
                     _ => unreachable!(),
                 });
             }
@@ -392,11 +791,11 @@ pub fn assemble<S: AsRef<str>>(
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
                 let operands = expression.into_inner();
-                insns.push(match rule {
-                    Rule::JumpIndexRegister => jinsn(pc, BPF_JMP | BPF_JEQ | BPF_X, false, operands, &labels)?,
-                    Rule::JumpIfIndexRegister => jtinsn(pc, BPF_JMP | BPF_JEQ | BPF_X, false, operands, &labels)?,
-                    Rule::JumpImmediate => jinsn(pc, BPF_JMP | BPF_JEQ | BPF_K, true, operands, &labels)?,
-                    Rule::JumpIfImmediate => jtinsn(pc, BPF_JMP | BPF_JEQ | BPF_K, true, operands, &labels)?,
+                jump = Some(match rule {
+                    Rule::JumpIndexRegister => jinsn(BPF_JMP | BPF_JEQ | BPF_X, false, operands)?,
+                    Rule::JumpIfIndexRegister => jinsn(BPF_JMP | BPF_JEQ | BPF_X, false, operands)?,
+                    Rule::JumpImmediate => jinsn(BPF_JMP | BPF_JEQ | BPF_K, true, operands)?,
+                    Rule::JumpIfImmediate => jinsn(BPF_JMP | BPF_JEQ | BPF_K, true, operands)?,
                     _ => unreachable!(),
                 });
             }
@@ -404,9 +803,9 @@ pub fn assemble<S: AsRef<str>>(
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
                 let operands = expression.into_inner();
-                insns.push(match rule {
-                    Rule::JumpIfIndexRegister => jfinsn(pc, BPF_JMP | BPF_JEQ | BPF_X, false, operands, &labels)?,
-                    Rule::JumpIfImmediate => jfinsn(pc, BPF_JMP | BPF_JEQ | BPF_K, true, operands, &labels)?,
+                jump = Some(match rule {
+                    Rule::JumpIfIndexRegister => jfinsn(BPF_JMP | BPF_JEQ | BPF_X, false, operands)?,
+                    Rule::JumpIfImmediate => jfinsn(BPF_JMP | BPF_JEQ | BPF_K, true, operands)?,
                     _ => unreachable!(),
                 });
             }
@@ -414,9 +813,9 @@ pub fn assemble<S: AsRef<str>>(
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
                 let operands = expression.into_inner();
-                insns.push(match rule {
-                    Rule::JumpIfIndexRegister => jfinsn(pc, BPF_JMP | BPF_JGE | BPF_X, false, operands, &labels)?,
-                    Rule::JumpIfImmediate => jfinsn(pc, BPF_JMP | BPF_JGE | BPF_K, true, operands, &labels)?,
+                jump = Some(match rule {
+                    Rule::JumpIfIndexRegister => jfinsn(BPF_JMP | BPF_JGE | BPF_X, false, operands)?,
+                    Rule::JumpIfImmediate => jfinsn(BPF_JMP | BPF_JGE | BPF_K, true, operands)?,
                     _ => unreachable!(),
                 });
             }
@@ -424,9 +823,9 @@ pub fn assemble<S: AsRef<str>>(
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
                 let operands = expression.into_inner();
-                insns.push(match rule {
-                    Rule::JumpIfIndexRegister => jfinsn(pc, BPF_JMP | BPF_JGT | BPF_X, false, operands, &labels)?,
-                    Rule::JumpIfImmediate => jfinsn(pc, BPF_JMP | BPF_JGT | BPF_K, true, operands, &labels)?,
+                jump = Some(match rule {
+                    Rule::JumpIfIndexRegister => jfinsn(BPF_JMP | BPF_JGT | BPF_X, false, operands)?,
+                    Rule::JumpIfImmediate => jfinsn(BPF_JMP | BPF_JGT | BPF_K, true, operands)?,
                     _ => unreachable!(),
                 });
             }
@@ -434,11 +833,11 @@ pub fn assemble<S: AsRef<str>>(
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
                 let operands = expression.into_inner();
-                insns.push(match rule {
-                    Rule::JumpIndexRegister => jinsn(pc, BPF_JMP | BPF_JGT | BPF_X, false, operands, &labels)?,
-                    Rule::JumpIfIndexRegister => jtinsn(pc, BPF_JMP | BPF_JGT | BPF_X, false, operands, &labels)?,
-                    Rule::JumpImmediate => jinsn(pc, BPF_JMP | BPF_JGT | BPF_K, true, operands, &labels)?,
-                    Rule::JumpIfImmediate => jtinsn(pc, BPF_JMP | BPF_JGT | BPF_K, true, operands, &labels)?,
+                jump = Some(match rule {
+                    Rule::JumpIndexRegister => jinsn(BPF_JMP | BPF_JGT | BPF_X, false, operands)?,
+                    Rule::JumpIfIndexRegister => jinsn(BPF_JMP | BPF_JGT | BPF_X, false, operands)?,
+                    Rule::JumpImmediate => jinsn(BPF_JMP | BPF_JGT | BPF_K, true, operands)?,
+                    Rule::JumpIfImmediate => jinsn(BPF_JMP | BPF_JGT | BPF_K, true, operands)?,
                     _ => unreachable!(),
                 });
             }
@@ -446,11 +845,11 @@ pub fn assemble<S: AsRef<str>>(
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
                 let operands = expression.into_inner();
-                insns.push(match rule {
-                    Rule::JumpIndexRegister => jinsn(pc, BPF_JMP | BPF_JGE | BPF_X, false, operands, &labels)?,
-                    Rule::JumpIfIndexRegister => jtinsn(pc, BPF_JMP | BPF_JGE | BPF_X, false, operands, &labels)?,
-                    Rule::JumpImmediate => jinsn(pc, BPF_JMP | BPF_JGE | BPF_K, true, operands, &labels)?,
-                    Rule::JumpIfImmediate => jtinsn(pc, BPF_JMP | BPF_JGE | BPF_K, true, operands, &labels)?,
+                jump = Some(match rule {
+                    Rule::JumpIndexRegister => jinsn(BPF_JMP | BPF_JGE | BPF_X, false, operands)?,
+                    Rule::JumpIfIndexRegister => jinsn(BPF_JMP | BPF_JGE | BPF_X, false, operands)?,
+                    Rule::JumpImmediate => jinsn(BPF_JMP | BPF_JGE | BPF_K, true, operands)?,
+                    Rule::JumpIfImmediate => jinsn(BPF_JMP | BPF_JGE | BPF_K, true, operands)?,
                     _ => unreachable!(),
                 });
             }
@@ -458,14 +857,25 @@ pub fn assemble<S: AsRef<str>>(
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
                 let operands = expression.into_inner();
-                insns.push(match rule {
-                    Rule::JumpIndexRegister => jinsn(pc, BPF_JMP | BPF_JSET | BPF_X, false, operands, &labels)?,
-                    Rule::JumpIfIndexRegister => jtinsn(pc, BPF_JMP | BPF_JSET | BPF_X, false, operands, &labels)?,
-                    Rule::JumpImmediate => jinsn(pc, BPF_JMP | BPF_JSET | BPF_K, true, operands, &labels)?,
-                    Rule::JumpIfImmediate => jtinsn(pc, BPF_JMP | BPF_JSET | BPF_K, true, operands, &labels)?,
+                jump = Some(match rule {
+                    Rule::JumpIndexRegister => jinsn(BPF_JMP | BPF_JSET | BPF_X, false, operands)?,
+                    Rule::JumpIfIndexRegister => jinsn(BPF_JMP | BPF_JSET | BPF_X, false, operands)?,
+                    Rule::JumpImmediate => jinsn(BPF_JMP | BPF_JSET | BPF_K, true, operands)?,
+                    Rule::JumpIfImmediate => jinsn(BPF_JMP | BPF_JSET | BPF_K, true, operands)?,
                     _ => unreachable!(),
                 });
             }
+            Rule::JNET => {
+                let expression = inner_pairs.next().unwrap();
+                let rule = expression.as_rule();
+                let operands = expression.into_inner();
+                let (prefix, jnet) = match rule {
+                    Rule::JumpNetwork => jnetinsns(operands)?,
+                    _ => unreachable!(),
+                };
+                insns.extend(prefix);
+                jump = Some(jnet);
+            }
             Rule::ADD => {
                 let expression = inner_pairs.next().unwrap();
                 let rule = expression.as_rule();
@@ -599,17 +1009,28 @@ pub fn assemble<S: AsRef<str>>(
                     _ => unreachable!(),
                 });
             }
-            Rule::Label => {
-                continue;
-            }
-            Rule::EOI => {
-                break;
-            }
+            Rule::Label | Rule::Var => return Ok(()),
             _ => unreachable!(),
         }
 
-        pc += 1;
-    }
+        if let Some(jump) = jump {
+            match jump.encode(insns.len(), &labels, relaxed.contains(&statement))? {
+                Some(encoded) => insns.extend(encoded),
+                None => {
+                    far.insert(statement);
+                    insns.push(Instruction::default());
+                }
+            }
+        }
+        statement += 1;
+
+        locations.resize(insns.len(), location);
+
+        Ok(())
+    })?;
+
+    let program =
+        Program { instructions: insns, debug_info: DebugInfo { locations, labels }, sources: preprocessor.sources };
 
-    Ok(insns)
+    Ok((program, far))
 }
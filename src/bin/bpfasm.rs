@@ -0,0 +1,86 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path;
+use std::process;
+use std::sync;
+
+const USAGE: &str = "usage: bpfasm run [--network] [--summary] [--write <output.pcap>] <program.bpf> <capture>";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut options = bpfasm::pcap::Options::default();
+    let mut summary_only = false;
+    let mut output_path = None;
+    let mut paths = Vec::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--network" => options.layer = bpfasm::pcap::Layer::Network,
+            "--summary" => summary_only = true,
+            "--write" => output_path = Some(args.next().ok_or(USAGE)?),
+            arg if arg.starts_with("--") => return Err(USAGE.to_string()),
+            _ => paths.push(arg),
+        }
+    }
+    let [program_path, capture_path] = paths[..] else {
+        return Err(USAGE.to_string());
+    };
+
+    let source = fs::read_to_string(program_path).map_err(|error| format!("{}: {}", program_path, error))?;
+    let directory = path::Path::new(program_path).parent().unwrap_or(path::Path::new("."));
+    let mut assembler = bpfasm::Assembler::default();
+    assembler.options.resolver = Some(sync::Arc::new(bpfasm::FileResolver::new([directory])));
+    let insns = assembler.assemble(&source).map_err(|error| error.with_path(program_path).to_string())?;
+
+    let input = fs::File::open(capture_path).map_err(|error| format!("{}: {}", capture_path, error))?;
+    let mut output = match output_path {
+        Some(path) => Some(io::BufWriter::new(fs::File::create(path).map_err(|error| format!("{}: {}", path, error))?)),
+        None => None,
+    };
+    let summary = bpfasm::pcap::run(
+        &insns,
+        io::BufReader::new(input),
+        output.as_mut().map(|output| output as &mut dyn Write),
+        &options,
+    )
+    .map_err(|error| format!("{}: {}", capture_path, error))?;
+    if let (Some(output), Some(path)) = (output.as_mut(), output_path) {
+        output.flush().map_err(|error| format!("{}: {}", path, error))?;
+    }
+
+    let mut stdout = io::stdout().lock();
+    let mut print = || -> io::Result<()> {
+        if !summary_only {
+            for packet in summary.packets.iter() {
+                writeln!(
+                    stdout,
+                    "{} {}.{:09} length {} captured {} result {}",
+                    packet.number,
+                    packet.timestamp.as_secs(),
+                    packet.timestamp.subsec_nanos(),
+                    packet.length,
+                    packet.captured,
+                    packet.result
+                )?;
+            }
+        }
+        writeln!(stdout, "{} accepted, {} dropped", summary.accepted, summary.dropped)
+    };
+    print().map_err(|error| error.to_string())
+}
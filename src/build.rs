@@ -0,0 +1,154 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Pre-assembles `.bpf` files from a build script, for crates that cannot use the `bpfasm-macros` crate
+//!
+//! ```no_run
+//! // in the main function of build.rs
+//! bpfasm::build::directory("filters");
+//! ```
+//!
+//! Each `filters/name.bpf` becomes a `pub const NAME: [bpfasm::Instruction; N]` in `$OUT_DIR/filters.rs`:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/filters.rs"));
+//! ```
+
+use std::collections;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path;
+use std::sync;
+
+use crate::disassembler::disassemble;
+use crate::*;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("{}: {source}", path.display())]
+    Io { path: path::PathBuf, source: io::Error },
+    #[error(transparent)]
+    Assembler(#[from] AssemblerError),
+}
+
+/// Assembles every `.bpf` file in `directory` with `Assembler::default()`, like `directory_with`, and fails the
+/// build script with the assembler's diagnostic if any of them cannot be assembled
+pub fn directory<P: AsRef<path::Path>>(directory: P) -> path::PathBuf {
+    match directory_with(directory, &Assembler::default()) {
+        Ok(path) => path,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Assembles every `.bpf` file directly inside `directory` with `assembler`, and writes them to a file in `OUT_DIR`
+/// named after the directory, as one `pub const` array per source named after the file in upper case. Prints
+/// `cargo:rerun-if-changed` for the directory, every source and every file they include. Includes are resolved
+/// relative to the including file, and then by the assembler's own resolver, if it has one. Returns the path of
+/// the generated file.
+pub fn directory_with<P: AsRef<path::Path>>(directory: P, assembler: &Assembler) -> Result<path::PathBuf, BuildError> {
+    let directory = directory.as_ref();
+    let io_error = |path: &path::Path| {
+        let path = path.to_path_buf();
+        move |source| BuildError::Io { path, source }
+    };
+
+    let out_directory = env::var_os("OUT_DIR").ok_or_else(|| BuildError::Io {
+        path: "OUT_DIR".into(),
+        source: io::Error::new(io::ErrorKind::NotFound, "not set; bpfasm::build must be called from a build script"),
+    })?;
+    let name = directory.file_name().unwrap_or_default().to_string_lossy();
+    let output = path::Path::new(&out_directory).join(format!("{}.rs", name));
+
+    println!("cargo:rerun-if-changed={}", directory.display());
+
+    let mut paths = Vec::default();
+    for entry in fs::read_dir(directory).map_err(io_error(directory))? {
+        let path = entry.map_err(io_error(directory))?.path();
+        if path.is_file() && path.extension().is_some_and(|extension| extension == "bpf") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut generated = String::new();
+    writeln!(generated, "// Generated by bpfasm::build from {}; do not edit", directory.display()).unwrap();
+
+    let mut constants = collections::HashSet::new();
+    for path in paths {
+        let source = fs::read_to_string(&path).map_err(io_error(&path))?;
+        println!("cargo:rerun-if-changed={}", path.display());
+
+        let resolver = sync::Arc::new(Tracker {
+            files: FileResolver::new(path.parent()),
+            fallback: assembler.options.resolver.clone(),
+            names: sync::Mutex::default(),
+        });
+        let options = Options { resolver: Some(resolver.clone()), ..assembler.options.clone() };
+        let result = assemble_with_debug_info(&source, &assembler.extensions, &options);
+        for name in resolver.names.lock().unwrap().iter() {
+            println!("cargo:rerun-if-changed={}", name);
+        }
+        let (insns, debug_info) = result.map_err(|error| error.with_path(&path.to_string_lossy()))?;
+
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let mut constant = stem.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>();
+        constant.make_ascii_uppercase();
+        if constant.starts_with(|c: char| c.is_ascii_digit()) {
+            constant.insert(0, '_');
+        }
+        if !constants.insert(constant.clone()) {
+            return Err(BuildError::Io {
+                path,
+                source: io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("another file is also named {}", constant),
+                ),
+            });
+        }
+
+        let target = |pc: usize| {
+            let names = debug_info.labels.iter().filter(|(_, i)| **i as usize == pc).map(|(name, _)| name);
+            names.min().cloned().unwrap_or_else(|| pc.to_string())
+        };
+
+        writeln!(generated).unwrap();
+        writeln!(generated, "/// Assembled from `{}`", path.file_name().unwrap_or_default().to_string_lossy()).unwrap();
+        writeln!(generated, "pub const {}: [::bpfasm::Instruction; {}] = [", constant, insns.len()).unwrap();
+        for (pc, insn) in insns.iter().enumerate() {
+            let Instruction { code, jt, jf, k } = insn;
+            let text = format!("    ::bpfasm::Instruction {{ code: {}, jt: {}, jf: {}, k: {} }},", code, jt, jf, k);
+            writeln!(generated, "{:<80} // {}", text, disassemble(insn, pc, &target)).unwrap();
+        }
+        writeln!(generated, "];").unwrap();
+    }
+
+    fs::write(&output, generated).map_err(io_error(&output))?;
+
+    Ok(output)
+}
+
+/// Resolves includes relative to the including file, falling back to another resolver, and remembers the name of
+/// every file it resolves
+struct Tracker {
+    files: FileResolver,
+    fallback: Option<sync::Arc<dyn SourceResolver + Send + Sync>>,
+    names: sync::Mutex<Vec<String>>,
+}
+
+impl SourceResolver for Tracker {
+    fn resolve(&self, name: &str, parent: Option<&str>) -> io::Result<Source> {
+        let source = match (self.files.resolve(name, parent), &self.fallback) {
+            (Err(error), Some(fallback)) if error.kind() == io::ErrorKind::NotFound => {
+                fallback.resolve(name, parent)?
+            }
+            (result, _) => result?,
+        };
+        self.names.lock().unwrap().push(source.name.clone());
+        Ok(source)
+    }
+}
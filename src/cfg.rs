@@ -0,0 +1,189 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Control-flow graphs of assembled programs
+
+use std::collections;
+use std::fmt::Write;
+
+use crate::disassembler::*;
+use crate::opcodes::*;
+use crate::*;
+
+/// How execution gets from one block to the next
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Branch {
+    /// The condition of the jump that ends the block was true
+    True,
+    /// The condition of the jump that ends the block was false
+    False,
+    /// The block ends in `ja`, or falls through into the next block
+    Unconditional,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Edge {
+    /// Index of the block that execution continues in
+    pub target: usize,
+    pub branch: Branch,
+}
+
+/// A run of instructions that is only entered at its first instruction and only left after its last
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// Program counter of the first instruction
+    pub start: usize,
+    /// Program counter after the last instruction
+    pub end: usize,
+    /// The true edge comes before the false edge, and a block ending in `ret` has no edges
+    pub edges: Vec<Edge>,
+}
+
+/// Splits a program into basic blocks, in program order. Returns `None` if a jump or the last instruction leaves
+/// the program.
+pub fn blocks(instructions: &[Instruction]) -> Option<Vec<Block>> {
+    let mut targets = Vec::with_capacity(instructions.len());
+    for (pc, insn) in instructions.iter().enumerate() {
+        let next = pc + 1;
+        let successors = match class(insn.code) {
+            BPF_RET => vec![],
+            BPF_JMP if op(insn.code) == BPF_JA => vec![(next.checked_add(insn.k as usize)?, Branch::Unconditional)],
+            BPF_JMP => vec![(next + insn.jt as usize, Branch::True), (next + insn.jf as usize, Branch::False)],
+            _ => vec![(next, Branch::Unconditional)],
+        };
+        if successors.iter().any(|(target, _)| *target >= instructions.len()) {
+            return None;
+        }
+        targets.push(successors);
+    }
+
+    let mut leaders = vec![false; instructions.len() + 1];
+    leaders[0] = true;
+    for (pc, successors) in targets.iter().enumerate() {
+        if class(instructions[pc].code) == BPF_JMP || class(instructions[pc].code) == BPF_RET {
+            leaders[pc + 1] = true;
+            for (target, _) in successors {
+                leaders[*target] = true;
+            }
+        }
+    }
+
+    let mut index = vec![0; instructions.len()];
+    let mut count = 0;
+    for pc in 0..instructions.len() {
+        if leaders[pc] {
+            count += 1;
+        }
+        index[pc] = count - 1;
+    }
+
+    let mut blocks: Vec<Block> = Vec::with_capacity(count);
+    for pc in 0..instructions.len() {
+        if leaders[pc] {
+            blocks.push(Block { start: pc, end: pc, edges: vec![] });
+        }
+        let block = blocks.last_mut().unwrap();
+        block.end = pc + 1;
+        block.edges =
+            targets[pc].iter().map(|(target, branch)| Edge { target: index[*target], branch: *branch }).collect();
+    }
+
+    Some(blocks)
+}
+
+/// Renders the control-flow graph of a program in Graphviz DOT format. Instructions are named after the labels in
+/// `labels` where one points at them, and blocks that end in `ret` are colored by the verdict they return under
+/// `profile`. Returns `None` if a jump or the last instruction leaves the program.
+pub fn dot(
+    instructions: &[Instruction],
+    labels: &collections::HashMap<String, u32>,
+    profile: Profile,
+) -> Option<String> {
+    let blocks = blocks(instructions)?;
+
+    let mut names = collections::BTreeMap::<usize, Vec<&str>>::new();
+    for (name, pc) in labels {
+        names.entry(*pc as usize).or_default().push(name);
+    }
+    for names in names.values_mut() {
+        names.sort();
+    }
+    let target = |pc: usize| match names.get(&pc) {
+        Some(names) => names[0].to_string(),
+        None => pc.to_string(),
+    };
+
+    let mut dot = String::new();
+    writeln!(dot, "digraph program {{").unwrap();
+    writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+    for (index, block) in blocks.iter().enumerate() {
+        let mut label = String::new();
+        for (pc, insn) in (block.start..).zip(&instructions[block.start..block.end]) {
+            for name in names.get(&pc).into_iter().flatten() {
+                write!(label, "{}:\\l", escape(name)).unwrap();
+            }
+            write!(label, "({}) {}\\l", pc, escape(&disassemble(insn, pc, &target))).unwrap();
+        }
+
+        let last = &instructions[block.end - 1];
+        let style = match class(last.code) {
+            BPF_RET => {
+                let (verdict, color) = verdict(last, profile);
+                write!(label, "{}\\l", verdict).unwrap();
+                format!(", style=filled, fillcolor=\"{}\"", color)
+            }
+            _ => String::new(),
+        };
+        writeln!(dot, "    block{} [label=\"{}\"{}];", index, label, style).unwrap();
+    }
+
+    for (index, block) in blocks.iter().enumerate() {
+        for edge in &block.edges {
+            let attributes = match edge.branch {
+                Branch::True => " [label=\"true\", color=\"darkgreen\"]",
+                Branch::False => " [label=\"false\", color=\"red\", style=\"dashed\"]",
+                Branch::Unconditional => "",
+            };
+            writeln!(dot, "    block{} -> block{}{};", index, edge.target, attributes).unwrap();
+        }
+    }
+
+    writeln!(dot, "}}").unwrap();
+
+    Some(dot)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Describes the value returned by a `ret` instruction, and picks a fill color for it
+fn verdict(insn: &Instruction, profile: Profile) -> (String, &'static str) {
+    match rval(insn.code) {
+        BPF_A => return ("returns A".to_string(), "lightgray"),
+        BPF_X => return ("returns X".to_string(), "lightgray"),
+        _ => {}
+    }
+
+    let k = insn.k;
+    match profile {
+        Profile::Socket => match k {
+            0 => ("drop".to_string(), "lightcoral"),
+            u32::MAX => ("accept".to_string(), "palegreen"),
+            _ => (format!("accept {} bytes", k), "palegreen"),
+        },
+        Profile::Seccomp => match k & 0xffff0000 {
+            0x80000000 => ("kill process".to_string(), "lightcoral"),
+            0x00000000 => ("kill thread".to_string(), "lightcoral"),
+            0x00030000 => (format!("trap {}", k & 0xffff), "khaki"),
+            0x00050000 => (format!("errno {}", k & 0xffff), "khaki"),
+            0x7fc00000 => ("user notification".to_string(), "khaki"),
+            0x7ff00000 => (format!("trace {}", k & 0xffff), "khaki"),
+            0x7ffc0000 => ("log".to_string(), "palegreen"),
+            0x7fff0000 => ("allow".to_string(), "palegreen"),
+            _ => (format!("unknown action {:#x}", k), "lightgray"),
+        },
+        Profile::Reuseport => (format!("socket {}", k), "lightblue"),
+    }
+}
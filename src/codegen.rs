@@ -0,0 +1,129 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Renders assembled programs as Rust, Go and C source code
+//!
+//! ```
+//! let source = "ldh [12]\nret a\n";
+//! let (insns, debug_info) = bpfasm::Assembler::default().assemble_with_debug_info(source).unwrap();
+//! let options = bpfasm::codegen::Options {
+//!     name: "ETHERTYPE".to_string(),
+//!     comments: Some(bpfasm::codegen::comments(source, &debug_info)),
+//! };
+//! assert_eq!(
+//!     bpfasm::codegen::rust(&insns, &options),
+//!     "pub const ETHERTYPE: [libc::sock_filter; 2] = [\n    \
+//!          libc::sock_filter { code: 0x0028, jt: 0x00, jf: 0x00, k: 0x0000000c }, // 1: ldh [12]\n    \
+//!          libc::sock_filter { code: 0x0016, jt: 0x00, jf: 0x00, k: 0x00000000 }, // 2: ret a\n\
+//!      ];\n"
+//! );
+//! ```
+
+use std::fmt::Write;
+
+use crate::*;
+
+/// How code is generated
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Identifier of the generated array. Go and C use it verbatim, and Rust in upper case, as its constants are
+    /// named.
+    pub name: String,
+    /// Comment to write after each instruction, indexed by program counter, such as the source lines returned by
+    /// `comments`. Empty comments are omitted.
+    pub comments: Option<Vec<String>>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { name: "filter".to_string(), comments: None }
+    }
+}
+
+/// Returns the source line that each instruction was assembled from, as `line: text`, or as `path:line` for
+/// instructions from included files. Instructions produced by the same statement as the previous one get an empty
+/// comment.
+pub fn comments(source: &str, debug_info: &DebugInfo) -> Vec<String> {
+    let mut previous = None;
+    let mut comments = Vec::with_capacity(debug_info.locations.len());
+    for location in debug_info.locations.iter() {
+        let comment = match (&location.path, previous == Some(location)) {
+            (_, true) => String::new(),
+            (Some(path), false) => format!("{}:{}", path, location.line),
+            (None, false) => {
+                let line = source.lines().nth(location.line - 1).unwrap_or_default();
+                format!("{}: {}", location.line, line.trim())
+            }
+        };
+        comments.push(comment);
+        previous = Some(location);
+    }
+    comments
+}
+
+/// Renders `insns` as a Rust `const` array of `libc::sock_filter`, named after `options.name` in upper case
+pub fn rust(insns: &[Instruction], options: &Options) -> String {
+    let mut code = String::new();
+    writeln!(code, "pub const {}: [libc::sock_filter; {}] = [", options.name.to_uppercase(), insns.len()).unwrap();
+    for (pc, insn) in insns.iter().enumerate() {
+        let Instruction { code: opcode, jt, jf, k } = insn;
+        write!(
+            code,
+            "    libc::sock_filter {{ code: {:#06x}, jt: {:#04x}, jf: {:#04x}, k: {:#010x} }},",
+            opcode, jt, jf, k
+        )
+        .unwrap();
+        writeln!(code, "{}", comment(options, pc, "//", "")).unwrap();
+    }
+    writeln!(code, "];").unwrap();
+    code
+}
+
+/// Renders `insns` as a Go `[]bpf.RawInstruction` literal for `golang.org/x/net/bpf`
+pub fn go(insns: &[Instruction], options: &Options) -> String {
+    let mut code = String::new();
+    writeln!(code, "var {} = []bpf.RawInstruction{{", options.name).unwrap();
+    for (pc, insn) in insns.iter().enumerate() {
+        let Instruction { code: opcode, jt, jf, k } = insn;
+        write!(code, "\t{{Op: {:#06x}, Jt: {:#04x}, Jf: {:#04x}, K: {:#010x}}},", opcode, jt, jf, k).unwrap();
+        writeln!(code, "{}", comment(options, pc, "//", "")).unwrap();
+    }
+    writeln!(code, "}}").unwrap();
+    code
+}
+
+/// Renders `insns` as a C `struct sock_filter` array, followed by a `struct sock_fprog` named after it with a
+/// `_prog` suffix
+pub fn c(insns: &[Instruction], options: &Options) -> String {
+    let name = &options.name;
+    let mut code = String::new();
+    writeln!(code, "static struct sock_filter {}[] = {{", name).unwrap();
+    for (pc, insn) in insns.iter().enumerate() {
+        let Instruction { code: opcode, jt, jf, k } = insn;
+        write!(code, "\t{{ {:#06x}, {:#04x}, {:#04x}, {:#010x} }},", opcode, jt, jf, k).unwrap();
+        writeln!(code, "{}", comment(options, pc, "/*", " */")).unwrap();
+    }
+    writeln!(code, "}};").unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "static struct sock_fprog {}_prog = {{", name).unwrap();
+    writeln!(code, "\t.len = sizeof({}) / sizeof({}[0]),", name, name).unwrap();
+    writeln!(code, "\t.filter = {},", name).unwrap();
+    writeln!(code, "}};").unwrap();
+    code
+}
+
+/// Returns the comment for the instruction at `pc`, preceded by a space, or nothing if it has none
+fn comment(options: &Options, pc: usize, open: &str, close: &str) -> String {
+    let comment = options.comments.as_ref().and_then(|comments| comments.get(pc)).map(|comment| comment.trim());
+    match comment {
+        Some(comment) if !comment.is_empty() => {
+            // keeps the comment on one line, and stops it from closing a block comment early
+            let mut comment = comment.replace(['\r', '\n'], " ");
+            if !close.is_empty() {
+                comment = comment.replace(close.trim(), "* /");
+            }
+            format!(" {} {}{}", open, comment, close)
+        }
+        _ => String::new(),
+    }
+}
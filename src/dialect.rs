@@ -0,0 +1,32 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fmt;
+
+/// The BPF implementation a program is assembled for, which determines the instructions it is allowed to contain.
+/// Every dialect rejects `ret x`, division or modulo by a constant zero, and programs that do not end with a return
+/// instruction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// The Linux kernel, which has no coprocessor or random number load, and rejects shifts by 32 or more
+    Linux,
+    /// FreeBSD, which has no ancillary data area, coprocessor or random number load
+    FreeBSD,
+    /// OpenBSD, which has no ancillary data area or coprocessor, but can load a random number with `BPF_RND`
+    OpenBSD,
+    /// NetBSD, which has no ancillary data area or random number load, but can call coprocessor functions
+    NetBSD,
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Dialect::Linux => "Linux",
+            Dialect::FreeBSD => "FreeBSD",
+            Dialect::OpenBSD => "OpenBSD",
+            Dialect::NetBSD => "NetBSD",
+        };
+        f.write_str(name)
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::opcodes::*;
+use crate::*;
+
+/// Renders the instruction at `pc` in assembler syntax, naming jump targets with `target`
+pub(crate) fn disassemble(insn: &Instruction, pc: usize, target: &dyn Fn(usize) -> String) -> String {
+    let k = insn.k;
+    let operand = match src(insn.code) {
+        BPF_X => "x".to_string(),
+        _ => immediate(k),
+    };
+    let suffix = match size(insn.code) {
+        BPF_H => "h",
+        BPF_B => "b",
+        _ => "",
+    };
+
+    match (class(insn.code), mode(insn.code)) {
+        (BPF_LD, BPF_IMM) if size(insn.code) == BPF_W => format!("ld {}", immediate(k)),
+        (BPF_LD, BPF_ABS) => format!("ld{} [{}]", suffix, k),
+        (BPF_LD, BPF_IND) => format!("ld{} [x + {}]", suffix, k),
+        (BPF_LD, BPF_MEM) if size(insn.code) == BPF_W => format!("ld M[{}]", k),
+        (BPF_LD, BPF_LEN) if size(insn.code) == BPF_W => "ld len".to_string(),
+        (BPF_LD, BPF_RND) if size(insn.code) == BPF_W => "ld rnd".to_string(),
+        (BPF_LDX, BPF_IMM) if size(insn.code) == BPF_W => format!("ldx {}", immediate(k)),
+        (BPF_LDX, BPF_MEM) if size(insn.code) == BPF_W => format!("ldx M[{}]", k),
+        (BPF_LDX, BPF_LEN) if size(insn.code) == BPF_W => "ldx len".to_string(),
+        (BPF_LDX, BPF_MSH) if size(insn.code) == BPF_B => format!("ldxb 4 * ([{}] & 0xf)", k),
+        (BPF_ST, _) => format!("st M[{}]", k),
+        (BPF_STX, _) => format!("stx M[{}]", k),
+        (BPF_ALU, _) => match op(insn.code) {
+            BPF_ADD => format!("add {}", operand),
+            BPF_SUB => format!("sub {}", operand),
+            BPF_MUL => format!("mul {}", operand),
+            BPF_DIV => format!("div {}", operand),
+            BPF_MOD => format!("mod {}", operand),
+            BPF_OR => format!("or {}", operand),
+            BPF_AND => format!("and {}", operand),
+            BPF_XOR => format!("xor {}", operand),
+            BPF_LSH => format!("lsh {}", operand),
+            BPF_RSH => format!("rsh {}", operand),
+            BPF_NEG => "neg".to_string(),
+            _ => unknown(insn),
+        },
+        (BPF_JMP, _) => {
+            let jt = target(pc + 1 + insn.jt as usize);
+            let jf = target(pc + 1 + insn.jf as usize);
+            match op(insn.code) {
+                BPF_JA => format!("ja {}", target((pc + 1).saturating_add(k as usize))),
+                BPF_JEQ => format!("jeq {}, {}, {}", operand, jt, jf),
+                BPF_JGT => format!("jgt {}, {}, {}", operand, jt, jf),
+                BPF_JGE => format!("jge {}, {}, {}", operand, jt, jf),
+                BPF_JSET => format!("jset {}, {}, {}", operand, jt, jf),
+                _ => unknown(insn),
+            }
+        }
+        (BPF_RET, _) => match rval(insn.code) {
+            BPF_A => "ret a".to_string(),
+            BPF_X => "ret x".to_string(),
+            _ => format!("ret {}", immediate(k)),
+        },
+        (BPF_MISC, _) => match miscop(insn.code) {
+            BPF_TAX => "tax".to_string(),
+            BPF_TXA => "txa".to_string(),
+            BPF_COP => format!("cop {}", immediate(k)),
+            BPF_COPX => "copx".to_string(),
+            _ => unknown(insn),
+        },
+        _ => unknown(insn),
+    }
+}
+
+fn immediate(k: u32) -> String {
+    match k {
+        0..=9 => format!("#{}", k),
+        _ => format!("#{:#x}", k),
+    }
+}
+
+fn unknown(insn: &Instruction) -> String {
+    format!("unknown {}", insn)
+}
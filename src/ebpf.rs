@@ -0,0 +1,301 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Converts classic BPF programs to eBPF socket filters, like the kernel's `bpf_convert_filter`
+//!
+//! A is kept in R0, X in R7 and the `__sk_buff` context in R6, and M[] is kept on the stack. Packet loads become
+//! eBPF's own `BPF_ABS` and `BPF_IND` loads, which end the program with a return value of 0 when they read past the
+//! end of the packet, like in classic BPF. Ancillary data loads read the matching `__sk_buff` field or call the
+//! matching helper function. Extensions that eBPF socket filters have no equivalent for, such as `hatype`, `nla`
+//! and `poff`, cannot be converted.
+
+use crate::disassembler::disassemble;
+use crate::opcodes::*;
+
+const R0: u8 = 0;
+const R1: u8 = 1;
+const R2: u8 = 2;
+const R6: u8 = 6;
+const R7: u8 = 7;
+const R10: u8 = 10;
+
+const A: u8 = R0;
+const X: u8 = R7;
+const TMP: u8 = R2;
+const CTX: u8 = R6;
+const FP: u8 = R10;
+
+const EBPF_ALU64: u8 = 0x07;
+const EBPF_MOV: u8 = 0xb0;
+const EBPF_END: u8 = 0xd0;
+const EBPF_TO_BE: u8 = 0x08;
+const EBPF_JNE: u8 = 0x50;
+const EBPF_JLT: u8 = 0xa0;
+const EBPF_JLE: u8 = 0xb0;
+const EBPF_CALL: u8 = 0x80;
+const EBPF_EXIT: u8 = 0x90;
+
+const BPF_FUNC_GET_PRANDOM_U32: i32 = 7;
+const BPF_FUNC_GET_SMP_PROCESSOR_ID: i32 = 8;
+
+const MEMORY: &str = "there is no such scratch memory word";
+
+/// An eBPF instruction
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub code: u8,
+    /// Destination register
+    pub dst: u8,
+    /// Source register
+    pub src: u8,
+    /// Signed offset of a memory access or jump
+    pub off: i16,
+    /// Signed immediate
+    pub imm: i32,
+}
+
+impl Instruction {
+    /// Returns the 8-byte encoding of the instruction in the given byte order
+    pub fn to_bytes(self, big_endian: bool) -> [u8; 8] {
+        let (registers, off, imm) = match big_endian {
+            true => (self.dst << 4 | self.src, self.off.to_be_bytes(), self.imm.to_be_bytes()),
+            false => (self.src << 4 | self.dst, self.off.to_le_bytes(), self.imm.to_le_bytes()),
+        };
+        [self.code, registers, off[0], off[1], imm[0], imm[1], imm[2], imm[3]]
+    }
+}
+
+/// Why a classic BPF program could not be converted
+#[derive(Debug, thiserror::Error)]
+#[error("instruction {pc} ({mnemonic}) cannot be converted to eBPF: {reason}")]
+pub struct ConvertError {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// The instruction in assembler syntax
+    pub mnemonic: String,
+    pub reason: String,
+}
+
+/// Converts the classic BPF socket filter `insns` to an eBPF socket filter
+pub fn convert(insns: &[crate::Instruction]) -> Result<Vec<Instruction>, ConvertError> {
+    let mut program = Vec::default();
+
+    // classic BPF starts with A and X cleared, and the verifier rejects reads of uninitialized stack slots
+    program.push(mov32_imm(A, 0));
+    program.push(mov32_imm(X, 0));
+    program.push(mov64_reg(CTX, R1));
+    let mut slots = insns
+        .iter()
+        .filter(|insn| matches!(class(insn.code), BPF_LD | BPF_LDX) && mode(insn.code) == BPF_MEM)
+        .map(|insn| insn.k)
+        .collect::<Vec<_>>();
+    slots.sort();
+    slots.dedup();
+    for off in slots.into_iter().filter_map(stack) {
+        program.push(Instruction { code: (BPF_ST | BPF_W | BPF_MEM) as u8, dst: FP, off, ..Default::default() });
+    }
+
+    // jumps are emitted with the classic program counter of their target, and patched once every instruction's
+    // position is known
+    let mut starts = Vec::with_capacity(insns.len() + 1);
+    let mut jumps = Vec::default();
+    for (pc, insn) in insns.iter().enumerate() {
+        starts.push(program.len());
+        let error = |reason: &str| ConvertError {
+            pc,
+            mnemonic: disassemble(insn, pc, &|target| target.to_string()),
+            reason: reason.to_string(),
+        };
+        let k = insn.k;
+        let next = pc + 1;
+
+        match class(insn.code) {
+            BPF_LD => match mode(insn.code) {
+                BPF_IMM => program.push(mov32_imm(A, k as i32)),
+                BPF_ABS if size(insn.code) == BPF_W && k >= SKF_AD_OFF => {
+                    program.extend(ancillary(k - SKF_AD_OFF).ok_or_else(|| error("unsupported extension"))?);
+                }
+                BPF_ABS => program.push(Instruction { code: insn.code as u8, imm: k as i32, ..Default::default() }),
+                BPF_IND => {
+                    program.push(Instruction { code: insn.code as u8, src: X, imm: k as i32, ..Default::default() })
+                }
+                BPF_MEM => program.push(ldx_mem(BPF_W, A, FP, stack(k).ok_or_else(|| error(MEMORY))?)),
+                BPF_LEN => program.push(ldx_mem(BPF_W, A, CTX, 0)),
+                _ => return Err(error("unsupported load")),
+            },
+            BPF_LDX => match mode(insn.code) {
+                BPF_IMM => program.push(mov32_imm(X, k as i32)),
+                BPF_MEM => program.push(ldx_mem(BPF_W, X, FP, stack(k).ok_or_else(|| error(MEMORY))?)),
+                BPF_LEN => program.push(ldx_mem(BPF_W, X, CTX, 0)),
+                BPF_MSH => {
+                    // the packet load clobbers R1 to R5, so A is saved in X, which is about to be overwritten anyway
+                    program.push(mov64_reg(X, A));
+                    program.push(Instruction {
+                        code: (BPF_LD | BPF_ABS | BPF_B) as u8,
+                        imm: k as i32,
+                        ..Default::default()
+                    });
+                    program.push(alu32_imm(BPF_AND, A, 0xf));
+                    program.push(alu32_imm(BPF_LSH, A, 2));
+                    program.push(mov64_reg(TMP, X));
+                    program.push(mov64_reg(X, A));
+                    program.push(mov64_reg(A, TMP));
+                }
+                _ => return Err(error("unsupported load")),
+            },
+            BPF_ST => program.push(stx_mem(BPF_W, FP, A, stack(k).ok_or_else(|| error(MEMORY))?)),
+            BPF_STX => program.push(stx_mem(BPF_W, FP, X, stack(k).ok_or_else(|| error(MEMORY))?)),
+            BPF_ALU => match (op(insn.code), src(insn.code)) {
+                (BPF_NEG, _) => {
+                    program.push(Instruction { code: (BPF_ALU | BPF_NEG) as u8, dst: A, ..Default::default() })
+                }
+                (BPF_DIV | BPF_MOD, BPF_X) => {
+                    // classic BPF returns 0 when dividing by zero, but eBPF carries on
+                    program.push(jmp_imm(EBPF_JNE, X, 0, 2));
+                    program.push(alu32_reg(BPF_XOR, A, A));
+                    program.push(exit());
+                    program.push(alu32_reg(op(insn.code), A, X));
+                }
+                (op, BPF_X) => program.push(alu32_reg(op, A, X)),
+                (op, _) => program.push(alu32_imm(op, A, k as i32)),
+            },
+            BPF_JMP if op(insn.code) == BPF_JA => {
+                jumps.push((program.len(), next.saturating_add(k as usize)));
+                program.push(ja(0));
+            }
+            BPF_JMP => {
+                let (jt, jf) = (next + insn.jt as usize, next + insn.jf as usize);
+                let (compare, src, imm) = match src(insn.code) {
+                    BPF_X => (BPF_X as u8, X, 0),
+                    // eBPF immediates are sign-extended to 64 bits, so those with the top bit set are compared
+                    // from a register instead
+                    _ if (k as i32) < 0 => {
+                        program.push(mov32_imm(TMP, k as i32));
+                        (BPF_X as u8, TMP, 0)
+                    }
+                    _ => (BPF_K as u8, 0, k as i32),
+                };
+                let (op, target, fallthrough) = match (op(insn.code), jt == next) {
+                    (BPF_JEQ, true) => (EBPF_JNE, jf, jt),
+                    (BPF_JGT, true) => (EBPF_JLE, jf, jt),
+                    (BPF_JGE, true) => (EBPF_JLT, jf, jt),
+                    (op, _) => (op as u8, jt, jf),
+                };
+                jumps.push((program.len(), target));
+                program.push(Instruction { code: BPF_JMP as u8 | op | compare, dst: A, src, off: 0, imm });
+                if fallthrough != next {
+                    jumps.push((program.len(), fallthrough));
+                    program.push(ja(0));
+                }
+            }
+            BPF_RET => {
+                match rval(insn.code) {
+                    BPF_A => {}
+                    BPF_X => program.push(mov64_reg(A, X)),
+                    _ => program.push(mov32_imm(A, k as i32)),
+                }
+                program.push(exit());
+            }
+            BPF_MISC => match miscop(insn.code) {
+                BPF_TAX => program.push(mov64_reg(X, A)),
+                BPF_TXA => program.push(mov64_reg(A, X)),
+                _ => return Err(error("eBPF has no coprocessor")),
+            },
+            _ => return Err(error("unknown instruction")),
+        }
+    }
+    starts.push(program.len());
+
+    for (index, target) in jumps {
+        let pc = starts.iter().rposition(|start| *start <= index).unwrap_or_default();
+        let target = *starts.get(target).ok_or_else(|| ConvertError {
+            pc,
+            mnemonic: disassemble(&insns[pc], pc, &|target| target.to_string()),
+            reason: "jump out of range".to_string(),
+        })?;
+        program[index].off = i16::try_from(target as isize - index as isize - 1).map_err(|_| ConvertError {
+            pc,
+            mnemonic: disassemble(&insns[pc], pc, &|target| target.to_string()),
+            reason: "jump is too far".to_string(),
+        })?;
+    }
+
+    Ok(program)
+}
+
+/// Returns the eBPF bytecode of `insns` in the byte order of the machine this crate was compiled for
+pub fn bytecode(insns: &[Instruction]) -> Vec<u8> {
+    insns.iter().flat_map(|insn| insn.to_bytes(cfg!(target_endian = "big"))).collect()
+}
+
+/// Returns the instructions that load the ancillary data at `offset` from `SKF_AD_OFF` into A
+fn ancillary(offset: u32) -> Option<Vec<Instruction>> {
+    // offsets of the fields of struct __sk_buff
+    let field = |offset: i16| vec![ldx_mem(BPF_W, A, CTX, offset)];
+    let network_order = |offset: i16| {
+        let swap = Instruction { code: (BPF_ALU as u8) | EBPF_END | EBPF_TO_BE, dst: A, imm: 16, ..Default::default() };
+        vec![ldx_mem(BPF_W, A, CTX, offset), swap]
+    };
+    let call =
+        |function: i32| vec![Instruction { code: BPF_JMP as u8 | EBPF_CALL, imm: function, ..Default::default() }];
+
+    match offset {
+        0 => Some(network_order(16)),                    // SKF_AD_PROTOCOL, __sk_buff.protocol
+        4 => Some(field(4)),                             // SKF_AD_PKTTYPE, __sk_buff.pkt_type
+        8 => Some(field(40)),                            // SKF_AD_IFINDEX, __sk_buff.ifindex
+        20 => Some(field(8)),                            // SKF_AD_MARK, __sk_buff.mark
+        24 => Some(field(12)),                           // SKF_AD_QUEUE, __sk_buff.queue_mapping
+        32 => Some(field(68)),                           // SKF_AD_RXHASH, __sk_buff.hash
+        36 => Some(call(BPF_FUNC_GET_SMP_PROCESSOR_ID)), // SKF_AD_CPU
+        40 => Some(vec![alu32_reg(BPF_XOR, A, X)]),      // SKF_AD_ALU_XOR_X
+        44 => Some(field(24)),                           // SKF_AD_VLAN_TAG, __sk_buff.vlan_tci
+        48 => Some(field(20)),                           // SKF_AD_VLAN_TAG_PRESENT, __sk_buff.vlan_present
+        56 => Some(call(BPF_FUNC_GET_PRANDOM_U32)),      // SKF_AD_RANDOM
+        60 => Some(network_order(28)),                   // SKF_AD_VLAN_TPID, __sk_buff.vlan_proto
+        _ => None,
+    }
+}
+
+/// Returns the offset of M[k] from the frame pointer, or `None` if there is no M[k]
+fn stack(k: u32) -> Option<i16> {
+    match (k as usize) < BPF_MEMWORDS {
+        true => Some(-((BPF_MEMWORDS - k as usize) as i16 * 4)),
+        false => None,
+    }
+}
+
+fn alu32_imm(op: u16, dst: u8, imm: i32) -> Instruction {
+    Instruction { code: (BPF_ALU | op | BPF_K) as u8, dst, imm, ..Default::default() }
+}
+
+fn alu32_reg(op: u16, dst: u8, src: u8) -> Instruction {
+    Instruction { code: (BPF_ALU | op | BPF_X) as u8, dst, src, ..Default::default() }
+}
+
+fn mov32_imm(dst: u8, imm: i32) -> Instruction {
+    Instruction { code: BPF_ALU as u8 | EBPF_MOV | BPF_K as u8, dst, imm, ..Default::default() }
+}
+
+fn mov64_reg(dst: u8, src: u8) -> Instruction {
+    Instruction { code: EBPF_ALU64 | EBPF_MOV | BPF_X as u8, dst, src, ..Default::default() }
+}
+
+fn ldx_mem(size: u16, dst: u8, src: u8, off: i16) -> Instruction {
+    Instruction { code: (BPF_LDX | size | BPF_MEM) as u8, dst, src, off, imm: 0 }
+}
+
+fn stx_mem(size: u16, dst: u8, src: u8, off: i16) -> Instruction {
+    Instruction { code: (BPF_STX | size | BPF_MEM) as u8, dst, src, off, imm: 0 }
+}
+
+fn jmp_imm(op: u8, dst: u8, imm: i32, off: i16) -> Instruction {
+    Instruction { code: BPF_JMP as u8 | op | BPF_K as u8, dst, src: 0, off, imm }
+}
+
+fn ja(off: i16) -> Instruction {
+    Instruction { code: (BPF_JMP | BPF_JA) as u8, off, ..Default::default() }
+}
+
+fn exit() -> Instruction {
+    Instruction { code: BPF_JMP as u8 | EBPF_EXIT, ..Default::default() }
+}
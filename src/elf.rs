@@ -0,0 +1,248 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Writes assembled programs as relocatable ELF objects that can be linked into C programs
+//!
+//! The object defines the program as a global `struct sock_filter` array, its number of instructions as a global
+//! `uint32_t` named after the array with a `_len` suffix, and optionally one global `struct sock_filter` per label,
+//! named after the array and the label:
+//!
+//! ```c
+//! extern const struct sock_filter filter[];
+//! extern const uint32_t filter_len;
+//! extern const struct sock_filter filter_drop;
+//! ```
+
+use std::collections;
+
+use crate::*;
+
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+const EM_BPF: u16 = 247;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+
+const STB_GLOBAL: u8 = 1;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+
+const EHDR_SIZE: usize = 64;
+const SHDR_SIZE: usize = 64;
+const SYM_SIZE: usize = 24;
+
+/// How the object is written
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Symbol name of the array, which is used verbatim
+    pub name: String,
+    /// Name of the read-only data section that holds the array and its length
+    pub section: String,
+    /// Labels to define symbols for, such as `DebugInfo::labels`. Labels that are not valid C identifiers, such as
+    /// those declared inside macro expansions, are skipped, as is a label named `len`, whose symbol would clash with
+    /// the length.
+    pub labels: collections::HashMap<String, u32>,
+    /// `e_machine` of the object. Defaults to the machine this crate was compiled for, or 0 (`EM_NONE`) if it is
+    /// not x86-64 or AArch64.
+    pub machine: u16,
+    /// Whether the object is big-endian. Defaults to the byte order this crate was compiled for.
+    pub big_endian: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let machine = match std::env::consts::ARCH {
+            "x86_64" => EM_X86_64,
+            "aarch64" => EM_AARCH64,
+            _ => 0,
+        };
+        Options {
+            name: "filter".to_string(),
+            section: ".rodata.bpf".to_string(),
+            labels: collections::HashMap::default(),
+            machine,
+            big_endian: cfg!(target_endian = "big"),
+        }
+    }
+}
+
+/// Returns a 64-bit relocatable ELF object containing `insns`
+pub fn object(insns: &[Instruction], options: &Options) -> Vec<u8> {
+    // the array is followed by its length
+    let mut data = Writer { bytes: Vec::default(), big_endian: options.big_endian };
+    for insn in insns.iter() {
+        data.u16(insn.code);
+        data.bytes.extend_from_slice(&[insn.jt, insn.jf]);
+        data.u32(insn.k);
+    }
+    let length_offset = data.bytes.len() as u64;
+    data.u32(insns.len() as u32);
+
+    let mut symbols = vec![(options.name.clone(), 0u64, length_offset, STT_OBJECT)];
+    symbols.push((format!("{}_len", options.name), length_offset, 4, STT_OBJECT));
+    let mut labels = options.labels.iter().filter(|(label, _)| identifier(label)).collect::<Vec<_>>();
+    labels.sort();
+    for (label, pc) in labels {
+        let name = format!("{}_{}", options.name, label);
+        if symbols.iter().any(|(symbol, ..)| *symbol == name) {
+            continue;
+        }
+        let size = match (*pc as usize) < insns.len() {
+            true => 8,
+            false => 0,
+        };
+        symbols.push((name, *pc as u64 * 8, size, STT_OBJECT));
+    }
+
+    write(&data.bytes, &symbols, SHF_ALLOC, options.machine, options)
+}
+
+/// Returns a 64-bit relocatable eBPF ELF object that defines `insns` as a function named after `options.name`, in an
+/// executable section named `options.section`. Loaders such as libbpf pick the program type from the section name,
+/// so it should be `socket` for socket filters. `options.labels` and `options.machine` are ignored.
+pub fn ebpf_object(insns: &[ebpf::Instruction], options: &Options) -> Vec<u8> {
+    let code = insns.iter().flat_map(|insn| insn.to_bytes(options.big_endian)).collect::<Vec<_>>();
+    let symbols = [(options.name.clone(), 0, code.len() as u64, STT_FUNC)];
+    write(&code, &symbols, SHF_ALLOC | SHF_EXECINSTR, EM_BPF, options)
+}
+
+/// Returns an object with one section holding `data`, and a global symbol in it for every `(name, offset, size,
+/// type)` in `symbols`
+fn write(data: &[u8], symbols: &[(String, u64, u64, u8)], flags: u64, machine: u16, options: &Options) -> Vec<u8> {
+    let mut elf = Writer { bytes: Vec::default(), big_endian: options.big_endian };
+
+    let mut strtab = Strings::default();
+    let names = symbols.iter().map(|(name, ..)| strtab.add(name)).collect::<Vec<_>>();
+
+    let mut shstrtab = Strings::default();
+    let sections = [
+        shstrtab.add(&options.section),
+        shstrtab.add(".note.GNU-stack"),
+        shstrtab.add(".symtab"),
+        shstrtab.add(".strtab"),
+        shstrtab.add(".shstrtab"),
+    ];
+
+    // header, then section contents, then section headers
+    elf.bytes.resize(EHDR_SIZE, 0);
+    let data_offset = elf.align(8);
+    elf.bytes.extend_from_slice(data);
+    let symtab_offset = elf.align(8);
+    elf.bytes.resize(elf.bytes.len() + SYM_SIZE, 0);
+    for ((_, value, size, kind), name) in symbols.iter().zip(names) {
+        elf.u32(name);
+        elf.bytes.push(STB_GLOBAL << 4 | kind);
+        elf.bytes.push(0);
+        elf.u16(1);
+        elf.u64(*value);
+        elf.u64(*size);
+    }
+    let strtab_offset = elf.bytes.len() as u64;
+    elf.bytes.extend_from_slice(&strtab.bytes);
+    let shstrtab_offset = elf.bytes.len() as u64;
+    elf.bytes.extend_from_slice(&shstrtab.bytes);
+    let section_headers = elf.align(8);
+
+    let symtab_size = (SYM_SIZE * (symbols.len() + 1)) as u64;
+    let sections = [
+        // name, type, flags, offset, size, link, info, align, entry size
+        (0, 0, 0, 0, 0, 0, 0, 0, 0),
+        (sections[0], SHT_PROGBITS, flags, data_offset, data.len() as u64, 0, 0, 8, 0),
+        (sections[1], SHT_PROGBITS, 0, data_offset, 0, 0, 0, 1, 0),
+        (sections[2], SHT_SYMTAB, 0, symtab_offset, symtab_size, 4, 1, 8, SYM_SIZE as u64),
+        (sections[3], SHT_STRTAB, 0, strtab_offset, strtab.bytes.len() as u64, 0, 0, 1, 0),
+        (sections[4], SHT_STRTAB, 0, shstrtab_offset, shstrtab.bytes.len() as u64, 0, 0, 1, 0),
+    ];
+    for (name, kind, flags, offset, size, link, info, align, entry_size) in sections {
+        elf.u32(name);
+        elf.u32(kind);
+        elf.u64(flags);
+        elf.u64(0);
+        elf.u64(offset);
+        elf.u64(size);
+        elf.u32(link);
+        elf.u32(info);
+        elf.u64(align);
+        elf.u64(entry_size);
+    }
+
+    let mut header = Writer { bytes: Vec::default(), big_endian: options.big_endian };
+    header.bytes.extend_from_slice(b"\x7fELF");
+    header.bytes.extend_from_slice(&[2, if options.big_endian { 2 } else { 1 }, 1, 0]);
+    header.bytes.resize(16, 0);
+    header.u16(1); // ET_REL
+    header.u16(machine);
+    header.u32(1);
+    header.u64(0);
+    header.u64(0);
+    header.u64(section_headers);
+    header.u32(0);
+    header.u16(EHDR_SIZE as u16);
+    header.u16(0);
+    header.u16(0);
+    header.u16(SHDR_SIZE as u16);
+    header.u16(sections.len() as u16);
+    header.u16(sections.len() as u16 - 1);
+    elf.bytes[..EHDR_SIZE].copy_from_slice(&header.bytes);
+
+    elf.bytes
+}
+
+/// Whether `name` is a valid C identifier
+fn identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+    big_endian: bool,
+}
+
+impl Writer {
+    fn u16(&mut self, value: u16) {
+        let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        self.bytes.extend_from_slice(&bytes);
+    }
+
+    fn u32(&mut self, value: u32) {
+        let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        self.bytes.extend_from_slice(&bytes);
+    }
+
+    fn u64(&mut self, value: u64) {
+        let bytes = if self.big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        self.bytes.extend_from_slice(&bytes);
+    }
+
+    /// Pads to a multiple of `alignment` and returns the new length
+    fn align(&mut self, alignment: usize) -> u64 {
+        self.bytes.resize(self.bytes.len().next_multiple_of(alignment), 0);
+        self.bytes.len() as u64
+    }
+}
+
+/// A string table, which starts with an empty string
+struct Strings {
+    bytes: Vec<u8>,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Strings { bytes: vec![0] }
+    }
+}
+
+impl Strings {
+    /// Appends `string` and returns its offset
+    fn add(&mut self, string: &str) -> u32 {
+        let offset = self.bytes.len() as u32;
+        self.bytes.extend_from_slice(string.as_bytes());
+        self.bytes.push(0);
+        offset
+    }
+}
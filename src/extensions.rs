@@ -2,34 +2,212 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use std::collections::*;
+use std::hash::BuildHasher;
 
+use crate::opcodes::*;
+
+/// Width of a packet load
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Width {
+    /// 32 bits, loaded by `ld`
+    Word,
+    /// 16 bits, loaded by `ldh`
+    Half,
+    /// 8 bits, loaded by `ldb`
+    Byte,
+}
+
+impl Width {
+    /// Returns the width loaded by the `BPF_LD` instruction `code`
+    pub(crate) fn of(code: u16) -> Width {
+        match size(code) {
+            BPF_W => Width::Word,
+            BPF_H => Width::Half,
+            _ => Width::Byte,
+        }
+    }
+
+    /// Returns the mnemonic of the instruction that loads this width into A
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Width::Word => "ld",
+            Width::Half => "ldh",
+            Width::Byte => "ldb",
+        }
+    }
+}
+
+/// Everything the assembler knows about a named extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extension {
+    /// Value of `k` that the name assembles to: an offset into the packet or ancillary data area for loads, or a
+    /// coprocessor function index for `cop`
+    pub offset: u32,
+    /// Widths the extension can be loaded with
+    pub widths: Vec<Width>,
+    /// Whether the extension names a coprocessor function that `cop` can call
+    pub cop: bool,
+    /// Instruction code that `ld` assembles to in place of an absolute word load, for extensions implemented as an
+    /// addressing mode of their own, such as OpenBSD's `BPF_RND`
+    pub code: Option<u16>,
+    /// First Linux kernel version that supports the extension, as `(major, minor, patch)`, if it is Linux-specific
+    pub min_kernel: Option<(u32, u32, u32)>,
+    /// Human-readable summary of what the extension loads or calls
+    pub description: String,
+}
+
+/// Looks up the extensions that operands such as `ld proto` and `cop name` refer to
+pub trait ExtensionResolver {
+    /// Returns the extension called `name`, or `None` if there is no such extension
+    fn resolve(&self, name: &str) -> Option<Extension>;
+}
+
+/// A plain table of names and `k` values, whose entries can be called by `cop` and loaded with any width, except
+/// that the Linux negative offsets from `SKF_LL_OFF` up cannot be called, and those into the ancillary data area can
+/// only be loaded as words
+impl<S: BuildHasher> ExtensionResolver for HashMap<String, u32, S> {
+    fn resolve(&self, name: &str) -> Option<Extension> {
+        self.get(name).map(|offset| Extension {
+            offset: *offset,
+            widths: match *offset >= SKF_AD_OFF {
+                true => vec![Width::Word],
+                false => vec![Width::Word, Width::Half, Width::Byte],
+            },
+            cop: *offset < SKF_LL_OFF,
+            code: None,
+            min_kernel: None,
+            description: String::new(),
+        })
+    }
+}
+
+impl<S: BuildHasher> ExtensionResolver for HashMap<String, Extension, S> {
+    fn resolve(&self, name: &str) -> Option<Extension> {
+        self.get(name).cloned()
+    }
+}
+
+impl<T: ExtensionResolver + ?Sized> ExtensionResolver for &T {
+    fn resolve(&self, name: &str) -> Option<Extension> {
+        (**self).resolve(name)
+    }
+}
+
+impl<T: ExtensionResolver + ?Sized> ExtensionResolver for Box<T> {
+    fn resolve(&self, name: &str) -> Option<Extension> {
+        (**self).resolve(name)
+    }
+}
+
+impl<T: ExtensionResolver + ?Sized> ExtensionResolver for std::sync::Arc<T> {
+    fn resolve(&self, name: &str) -> Option<Extension> {
+        (**self).resolve(name)
+    }
+}
+
+/// Names and `k` values of the Linux extensions in `linux_table`
 pub fn linux() -> HashMap<String, u32> {
+    linux_table().into_iter().map(|(name, extension)| (name, extension.offset)).collect()
+}
+
+/// Linux ancillary data loads, which read from `SKF_AD_OFF` (0xfffff000) plus an offset and are only recognized by
+/// the kernel as 32-bit `ld` instructions, along with the `SKF_NET_OFF` and `SKF_LL_OFF` bases of the network and
+/// link-layer headers
+pub fn linux_table() -> HashMap<String, Extension> {
+    linux_kernel((u32::MAX, u32::MAX, u32::MAX))
+}
+
+/// Like `linux_table`, but only includes the extensions available in kernel `version`, given as
+/// `(major, minor, patch)`
+pub fn linux_kernel(version: (u32, u32, u32)) -> HashMap<String, Extension> {
     let mut extensions = HashMap::default();
 
-    extensions.insert("pto".to_string(), 0xFFFFF000 + 0); // SKF_AD_PROTOCOL
-    extensions.insert("proto".to_string(), 0xFFFFF000 + 0); // SKF_AD_PROTOCOL
-    extensions.insert("type".to_string(), 0xFFFFF000 + 4); // SKF_AD_PKTTYPE
-    extensions.insert("poff".to_string(), 0xFFFFF000 + 52); // SKF_AD_PAY_OFFSET
-    extensions.insert("ifx".to_string(), 0xFFFFF000 + 8); // SKF_AD_IFINDEX
-    extensions.insert("ifidx".to_string(), 0xFFFFF000 + 8); // SKF_AD_IFINDEX
-    extensions.insert("nla".to_string(), 0xFFFFF000 + 12); // SKF_AD_NLATTR
-    extensions.insert("nlan".to_string(), 0xFFFFF000 + 16); // SKF_AD_NLATTR_NEST
-    extensions.insert("mark".to_string(), 0xFFFFF000 + 20); // SKF_AD_MARK
-    extensions.insert("Q".to_string(), 0xFFFFF000 + 24); // SKF_AD_QUEUE
-    extensions.insert("que".to_string(), 0xFFFFF000 + 24); // SKF_AD_QUEUE
-    extensions.insert("queue".to_string(), 0xFFFFF000 + 24); // SKF_AD_QUEUE
-    extensions.insert("hat".to_string(), 0xFFFFF000 + 28); // SKF_AD_HATYPE
-    extensions.insert("hatype".to_string(), 0xFFFFF000 + 28); // SKF_AD_HATYPE
-    extensions.insert("rxh".to_string(), 0xFFFFF000 + 32); // SKF_AD_RXHASH
-    extensions.insert("rxhash".to_string(), 0xFFFFF000 + 32); // SKF_AD_RXHASH
-    extensions.insert("cpu".to_string(), 0xFFFFF000 + 36); // SKF_AD_CPU
-    extensions.insert("vlant".to_string(), 0xFFFFF000 + 44); // SKF_AD_VLAN_TAG
-    extensions.insert("vlan_tci".to_string(), 0xFFFFF000 + 44); // SKF_AD_VLAN_TAG
-    extensions.insert("vlanp".to_string(), 0xFFFFF000 + 48); // SKF_AD_VLAN_TAG_PRESENT
-    extensions.insert("vlan_pr".to_string(), 0xFFFFF000 + 48); // SKF_AD_VLAN_TAG_PRESENT
-    extensions.insert("vlan_avail".to_string(), 0xFFFFF000 + 48); // SKF_AD_VLAN_TAG_PRESENT
-    extensions.insert("vlan_tpid".to_string(), 0xFFFFF000 + 60); // SKF_AD_VLAN_TPID
-    extensions.insert("rand".to_string(), 0xFFFFF000 + 56); // SKF_AD_RANDOM
+    let mut insert = |names: &[&str], offset: u32, widths: &[Width], min_kernel: (u32, u32, u32), description: &str| {
+        if min_kernel > version {
+            return;
+        }
+        for name in names {
+            let extension = Extension {
+                offset,
+                widths: widths.to_vec(),
+                cop: false,
+                code: None,
+                min_kernel: Some(min_kernel),
+                description: description.to_string(),
+            };
+            extensions.insert(name.to_string(), extension);
+        }
+    };
+
+    let ad = |offset: u32| SKF_AD_OFF + offset;
+    let word = &[Width::Word];
+    let any = &[Width::Word, Width::Half, Width::Byte];
+
+    insert(&["pto", "proto"], ad(0), word, (2, 4, 0), "skb->protocol"); // SKF_AD_PROTOCOL
+    insert(&["type"], ad(4), word, (2, 4, 0), "skb->pkt_type"); // SKF_AD_PKTTYPE
+    insert(&["ifx", "ifidx"], ad(8), word, (2, 4, 0), "skb->dev->ifindex"); // SKF_AD_IFINDEX
+    insert(&["nla"], ad(12), word, (2, 6, 24), "offset of the netlink attribute of type X"); // SKF_AD_NLATTR
+    insert(&["nlan"], ad(16), word, (2, 6, 26), "offset of the nested netlink attribute of type X"); // SKF_AD_NLATTR_NEST
+    insert(&["mark"], ad(20), word, (2, 6, 33), "skb->mark"); // SKF_AD_MARK
+    insert(&["Q", "que", "queue"], ad(24), word, (2, 6, 33), "skb->queue_mapping"); // SKF_AD_QUEUE
+    insert(&["hat", "hatype"], ad(28), word, (2, 6, 34), "skb->dev->type"); // SKF_AD_HATYPE
+    insert(&["rxh", "rxhash"], ad(32), word, (2, 6, 34), "skb->hash"); // SKF_AD_RXHASH
+    insert(&["cpu"], ad(36), word, (2, 6, 36), "raw_smp_processor_id()"); // SKF_AD_CPU
+    insert(&["alu_xor_x"], ad(40), word, (3, 6, 0), "A ^ X"); // SKF_AD_ALU_XOR_X
+    insert(&["vlant", "vlan_tci"], ad(44), word, (3, 8, 0), "skb_vlan_tag_get(skb)"); // SKF_AD_VLAN_TAG
+    insert(&["vlanp", "vlan_pr", "vlan_avail"], ad(48), word, (3, 8, 0), "skb_vlan_tag_present(skb)"); // SKF_AD_VLAN_TAG_PRESENT
+    insert(&["poff"], ad(52), word, (3, 13, 0), "offset of the transport payload"); // SKF_AD_PAY_OFFSET
+    insert(&["rand"], ad(56), word, (3, 15, 0), "prandom_u32()"); // SKF_AD_RANDOM
+    insert(&["vlan_tpid"], ad(60), word, (4, 4, 0), "skb->vlan_proto"); // SKF_AD_VLAN_TPID
+
+    insert(&["net_off"], 0xFFF00000, any, (2, 6, 0), "start of the network header"); // SKF_NET_OFF
+    insert(&["ll_off"], 0xFFE00000, any, (2, 6, 0), "start of the link-layer header"); // SKF_LL_OFF
+
+    extensions
+}
+
+/// FreeBSD has no ancillary data area and no coprocessor, so it has no extensions
+pub fn freebsd() -> HashMap<String, Extension> {
+    HashMap::default()
+}
+
+/// OpenBSD's `BPF_RND` load, which sets A to a random number
+pub fn openbsd() -> HashMap<String, Extension> {
+    let mut extensions = HashMap::default();
+
+    for name in ["rnd", "random"] {
+        let extension = Extension {
+            offset: 0,
+            widths: vec![Width::Word],
+            cop: false,
+            code: Some(BPF_LD | BPF_W | BPF_RND),
+            min_kernel: None,
+            description: "arc4random()".to_string(),
+        };
+        extensions.insert(name.to_string(), extension);
+    }
+
+    extensions
+}
+
+/// NetBSD's coprocessor functions, as registered by NPF with `bpf_set_cop()` and called by `cop`
+pub fn netbsd() -> HashMap<String, Extension> {
+    let mut extensions = HashMap::default();
+
+    let mut insert = |name: &str, index: u32, description: &str| {
+        let extension = Extension {
+            offset: index,
+            widths: Vec::default(),
+            cop: true,
+            code: None,
+            min_kernel: None,
+            description: description.to_string(),
+        };
+        extensions.insert(name.to_string(), extension);
+    };
+
+    insert("npf_cop_l3", 0, "fill in the L3 header information of the packet"); // NPF_COP_L3
+    insert("npf_cop_table", 1, "look up the address in M[] in the NPF table numbered A"); // NPF_COP_TABLE
 
     extensions
 }
@@ -0,0 +1,194 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Runs programs over packets held in memory, with the same results as a Linux socket filter
+//!
+//! Packets are whole frames, and `len` is the length of the packet. Loads that read past the end of the packet end
+//! the program with a return value of 0, as does division or modulo by a zero X. Shifts by X only use the low five
+//! bits of X. There is no ancillary data, so `check` rejects loads from it.
+
+use crate::opcodes::*;
+use crate::*;
+
+/// Why a program cannot be run
+#[derive(Debug, thiserror::Error)]
+#[error("instruction {pc} cannot be run: {reason}")]
+pub struct CheckError {
+    /// Program counter of the instruction
+    pub pc: usize,
+    pub reason: String,
+}
+
+/// Checks that `insns` is a program that the kernel would accept as a socket filter, other than loads from the
+/// ancillary data area, which cannot be run outside the kernel
+pub fn check(insns: &[Instruction]) -> Result<(), CheckError> {
+    let error = |pc: usize, reason: &str| Err(CheckError { pc, reason: reason.to_string() });
+
+    if insns.is_empty() {
+        return error(0, "the program is empty");
+    }
+    if insns.len() > 4096 {
+        return error(4096, "the program is longer than 4096 instructions");
+    }
+
+    for (pc, insn) in insns.iter().enumerate() {
+        let k = insn.k;
+        let next = pc + 1;
+        let valid = insn.code <= 0xff;
+        let valid = valid
+            && match class(insn.code) {
+                BPF_LD => match (mode(insn.code), size(insn.code)) {
+                    (BPF_ABS, BPF_W | BPF_H | BPF_B) if k >= SKF_LL_OFF => {
+                        return error(pc, "ancillary data is not available");
+                    }
+                    (BPF_ABS | BPF_IND, BPF_W | BPF_H | BPF_B) => true,
+                    (BPF_IMM | BPF_LEN, BPF_W) => true,
+                    (BPF_MEM, BPF_W) => (k as usize) < BPF_MEMWORDS,
+                    _ => false,
+                },
+                BPF_LDX => match (mode(insn.code), size(insn.code)) {
+                    (BPF_MSH, BPF_B) if k >= SKF_LL_OFF => return error(pc, "ancillary data is not available"),
+                    (BPF_IMM | BPF_LEN, BPF_W) | (BPF_MSH, BPF_B) => true,
+                    (BPF_MEM, BPF_W) => (k as usize) < BPF_MEMWORDS,
+                    _ => false,
+                },
+                BPF_ST | BPF_STX => insn.code <= BPF_STX && (k as usize) < BPF_MEMWORDS,
+                BPF_ALU => match (op(insn.code), src(insn.code)) {
+                    (BPF_DIV | BPF_MOD, BPF_K) if k == 0 => return error(pc, "division by zero"),
+                    (BPF_LSH | BPF_RSH, BPF_K) if k >= 32 => return error(pc, "shift by 32 or more bits"),
+                    (BPF_NEG, BPF_K) => true,
+                    (BPF_ADD | BPF_SUB | BPF_MUL | BPF_DIV | BPF_MOD | BPF_OR | BPF_AND | BPF_XOR, _) => true,
+                    (BPF_LSH | BPF_RSH, _) => true,
+                    _ => false,
+                },
+                BPF_JMP => match op(insn.code) {
+                    BPF_JA if src(insn.code) == BPF_K => match next.checked_add(k as usize) {
+                        Some(target) if target < insns.len() => true,
+                        _ => return error(pc, "jump out of range"),
+                    },
+                    BPF_JEQ | BPF_JGT | BPF_JGE | BPF_JSET => {
+                        if next + insn.jt.max(insn.jf) as usize >= insns.len() {
+                            return error(pc, "jump out of range");
+                        }
+                        true
+                    }
+                    _ => false,
+                },
+                BPF_RET => insn.code == BPF_RET | BPF_K || insn.code == BPF_RET | BPF_A,
+                BPF_MISC => insn.code == BPF_MISC | BPF_TAX || insn.code == BPF_MISC | BPF_TXA,
+                _ => false,
+            };
+        if !valid {
+            return error(pc, "unsupported instruction");
+        }
+    }
+
+    match class(insns[insns.len() - 1].code) {
+        BPF_RET => Ok(()),
+        _ => error(insns.len() - 1, "the program does not end with a return instruction"),
+    }
+}
+
+/// Runs `insns` over `packet` and returns the result. Instructions that `check` rejects end the program with a
+/// return value of 0.
+pub fn run(insns: &[Instruction], packet: &[u8]) -> u32 {
+    let (mut a, mut x) = (0u32, 0u32);
+    let mut memory = [0u32; BPF_MEMWORDS];
+    let mut pc = 0;
+
+    let load = |offset: u32, size: u16| {
+        let offset = offset as usize;
+        let bytes = match size {
+            BPF_W => packet.get(offset..offset.checked_add(4)?)?,
+            BPF_H => packet.get(offset..offset.checked_add(2)?)?,
+            _ => packet.get(offset..offset.checked_add(1)?)?,
+        };
+        Some(bytes.iter().fold(0u32, |value, byte| value << 8 | *byte as u32))
+    };
+
+    while let Some(insn) = insns.get(pc) {
+        let k = insn.k;
+        pc += 1;
+
+        match class(insn.code) {
+            BPF_LD => {
+                a = match mode(insn.code) {
+                    BPF_ABS if k < SKF_LL_OFF => match load(k, size(insn.code)) {
+                        Some(value) => value,
+                        None => return 0,
+                    },
+                    BPF_IND => match load(x.wrapping_add(k), size(insn.code)) {
+                        Some(value) => value,
+                        None => return 0,
+                    },
+                    BPF_IMM => k,
+                    BPF_LEN => packet.len() as u32,
+                    BPF_MEM if (k as usize) < BPF_MEMWORDS => memory[k as usize],
+                    _ => return 0,
+                }
+            }
+            BPF_LDX => {
+                x = match mode(insn.code) {
+                    BPF_MSH if k < SKF_LL_OFF => match load(k, BPF_B) {
+                        Some(value) => (value & 0xf) << 2,
+                        None => return 0,
+                    },
+                    BPF_IMM => k,
+                    BPF_LEN => packet.len() as u32,
+                    BPF_MEM if (k as usize) < BPF_MEMWORDS => memory[k as usize],
+                    _ => return 0,
+                }
+            }
+            BPF_ST | BPF_STX if (k as usize) < BPF_MEMWORDS => {
+                memory[k as usize] = if class(insn.code) == BPF_ST { a } else { x };
+            }
+            BPF_ALU => {
+                let operand = if src(insn.code) == BPF_X { x } else { k };
+                a = match op(insn.code) {
+                    BPF_ADD => a.wrapping_add(operand),
+                    BPF_SUB => a.wrapping_sub(operand),
+                    BPF_MUL => a.wrapping_mul(operand),
+                    BPF_DIV if operand != 0 => a / operand,
+                    BPF_MOD if operand != 0 => a % operand,
+                    BPF_OR => a | operand,
+                    BPF_AND => a & operand,
+                    BPF_XOR => a ^ operand,
+                    BPF_LSH => a << (operand & 31),
+                    BPF_RSH => a >> (operand & 31),
+                    BPF_NEG => a.wrapping_neg(),
+                    _ => return 0,
+                }
+            }
+            BPF_JMP => {
+                let operand = if src(insn.code) == BPF_X { x } else { k };
+                let taken = match op(insn.code) {
+                    BPF_JA => {
+                        pc = pc.saturating_add(k as usize);
+                        continue;
+                    }
+                    BPF_JEQ => a == operand,
+                    BPF_JGT => a > operand,
+                    BPF_JGE => a >= operand,
+                    BPF_JSET => a & operand != 0,
+                    _ => return 0,
+                };
+                pc += if taken { insn.jt } else { insn.jf } as usize;
+            }
+            BPF_RET => {
+                return match rval(insn.code) {
+                    BPF_A => a,
+                    BPF_K => k,
+                    _ => 0,
+                }
+            }
+            BPF_MISC => match miscop(insn.code) {
+                BPF_TAX => x = a,
+                BPF_TXA => a = x,
+                _ => return 0,
+            },
+            _ => return 0,
+        }
+    }
+
+    0
+}
@@ -0,0 +1,266 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Compiles programs to x86-64 machine code, for running them over many packets faster than `interpreter::run`,
+//! with the same results
+//!
+//! A is kept in `eax`, X in `r8d` and M[] on the stack. The packet is passed in `rdi` and its length in `rsi`.
+
+use std::io;
+use std::ptr;
+
+use crate::interpreter::{check, CheckError};
+use crate::opcodes::*;
+use crate::*;
+
+#[derive(Debug, thiserror::Error)]
+pub enum JitError {
+    #[error(transparent)]
+    Check(#[from] CheckError),
+    #[error("cannot map executable memory: {0}")]
+    Memory(io::Error),
+}
+
+/// A program compiled to machine code
+pub struct Jit {
+    code: ptr::NonNull<libc::c_void>,
+    length: usize,
+}
+
+// the code is never written to after it is compiled
+unsafe impl Send for Jit {}
+unsafe impl Sync for Jit {}
+
+impl Jit {
+    /// Compiles `insns`, which must pass `interpreter::check`
+    pub fn compile(insns: &[Instruction]) -> Result<Jit, JitError> {
+        check(insns)?;
+        let code = emit(insns);
+
+        unsafe {
+            let length = code.len();
+            let memory = libc::mmap(
+                ptr::null_mut(),
+                length,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if memory == libc::MAP_FAILED {
+                return Err(JitError::Memory(io::Error::last_os_error()));
+            }
+            ptr::copy_nonoverlapping(code.as_ptr(), memory.cast::<u8>(), length);
+            let jit = Jit { code: ptr::NonNull::new_unchecked(memory), length };
+            if libc::mprotect(memory, length, libc::PROT_READ | libc::PROT_EXEC) != 0 {
+                return Err(JitError::Memory(io::Error::last_os_error()));
+            }
+            Ok(jit)
+        }
+    }
+
+    /// Runs the program over `packet` and returns the result
+    pub fn run(&self, packet: &[u8]) -> u32 {
+        let function: extern "sysv64" fn(*const u8, usize) -> u32 = unsafe { std::mem::transmute(self.code.as_ptr()) };
+        function(packet.as_ptr(), packet.len())
+    }
+}
+
+impl Drop for Jit {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.code.as_ptr(), self.length);
+        }
+    }
+}
+
+/// Where a `rel32` operand needs to point once the machine code of every instruction is placed
+enum Target {
+    /// The instruction at a program counter
+    Instruction(usize),
+    /// The code that returns 0
+    Abort,
+}
+
+/// Returns the machine code of `insns`, which must pass `check`
+fn emit(insns: &[Instruction]) -> Vec<u8> {
+    let mut code = Vec::default();
+    let mut starts = Vec::with_capacity(insns.len());
+    let mut fixups = Vec::default();
+
+    // A = 0, X = 0, and M[] is zeroed on the stack
+    code.extend_from_slice(&[0x31, 0xc0]); // xor eax, eax
+    code.extend_from_slice(&[0x45, 0x31, 0xc0]); // xor r8d, r8d
+    code.extend_from_slice(&[0x45, 0x31, 0xc9]); // xor r9d, r9d
+    code.extend_from_slice(&[0x48, 0x83, 0xec, 0x40]); // sub rsp, 64
+    for offset in (0..64).step_by(8) {
+        code.extend_from_slice(&[0x4c, 0x89, 0x4c, 0x24, offset]); // mov [rsp + offset], r9
+    }
+
+    for (pc, insn) in insns.iter().enumerate() {
+        starts.push(code.len());
+        let k = insn.k;
+        let next = pc + 1;
+        let slot = (k as u8).wrapping_mul(4);
+
+        match (class(insn.code), mode(insn.code)) {
+            (BPF_LD, BPF_IMM) => {
+                code.push(0xb8); // mov eax, k
+                code.extend_from_slice(&k.to_le_bytes());
+            }
+            (BPF_LD, BPF_LEN) => code.extend_from_slice(&[0x89, 0xf0]), // mov eax, esi
+            (BPF_LD, BPF_MEM) => code.extend_from_slice(&[0x8b, 0x44, 0x24, slot]), // mov eax, [rsp + slot]
+            (BPF_LD, BPF_ABS | BPF_IND) => {
+                code.extend_from_slice(&[0x41, 0xb9]); // mov r9d, k
+                code.extend_from_slice(&k.to_le_bytes());
+                if mode(insn.code) == BPF_IND {
+                    code.extend_from_slice(&[0x45, 0x01, 0xc1]); // add r9d, r8d
+                }
+                let width = match size(insn.code) {
+                    BPF_W => 4,
+                    BPF_H => 2,
+                    _ => 1,
+                };
+                bounds(&mut code, &mut fixups, width);
+                match width {
+                    // mov eax, [rdi + r9]; bswap eax
+                    4 => code.extend_from_slice(&[0x42, 0x8b, 0x04, 0x0f, 0x0f, 0xc8]),
+                    // movzx eax, word [rdi + r9]; rol ax, 8
+                    2 => code.extend_from_slice(&[0x42, 0x0f, 0xb7, 0x04, 0x0f, 0x66, 0xc1, 0xc0, 0x08]),
+                    _ => code.extend_from_slice(&[0x42, 0x0f, 0xb6, 0x04, 0x0f]), // movzx eax, byte [rdi + r9]
+                }
+            }
+            (BPF_LDX, BPF_IMM) => {
+                code.extend_from_slice(&[0x41, 0xb8]); // mov r8d, k
+                code.extend_from_slice(&k.to_le_bytes());
+            }
+            (BPF_LDX, BPF_LEN) => code.extend_from_slice(&[0x41, 0x89, 0xf0]), // mov r8d, esi
+            (BPF_LDX, BPF_MEM) => code.extend_from_slice(&[0x44, 0x8b, 0x44, 0x24, slot]), // mov r8d, [rsp + slot]
+            (BPF_LDX, _) => {
+                code.extend_from_slice(&[0x41, 0xb9]); // mov r9d, k
+                code.extend_from_slice(&k.to_le_bytes());
+                bounds(&mut code, &mut fixups, 1);
+                code.extend_from_slice(&[0x46, 0x0f, 0xb6, 0x04, 0x0f]); // movzx r8d, byte [rdi + r9]
+                code.extend_from_slice(&[0x41, 0x83, 0xe0, 0x0f]); // and r8d, 0xf
+                code.extend_from_slice(&[0x41, 0xc1, 0xe0, 0x02]); // shl r8d, 2
+            }
+            (BPF_ST, _) => code.extend_from_slice(&[0x89, 0x44, 0x24, slot]), // mov [rsp + slot], eax
+            (BPF_STX, _) => code.extend_from_slice(&[0x44, 0x89, 0x44, 0x24, slot]), // mov [rsp + slot], r8d
+            (BPF_ALU, _) => alu(&mut code, &mut fixups, insn),
+            (BPF_JMP, _) if op(insn.code) == BPF_JA => {
+                code.push(0xe9); // jmp
+                fixups.push((code.len(), Target::Instruction(next + k as usize)));
+                code.extend_from_slice(&[0; 4]);
+            }
+            (BPF_JMP, _) => {
+                let x = src(insn.code) == BPF_X;
+                match (op(insn.code), x) {
+                    (BPF_JSET, true) => code.extend_from_slice(&[0x44, 0x85, 0xc0]), // test eax, r8d
+                    (BPF_JSET, false) => code.push(0xa9),                            // test eax, k
+                    (_, true) => code.extend_from_slice(&[0x44, 0x39, 0xc0]),        // cmp eax, r8d
+                    (_, false) => code.push(0x3d),                                   // cmp eax, k
+                }
+                if !x {
+                    code.extend_from_slice(&k.to_le_bytes());
+                }
+                let condition = match op(insn.code) {
+                    BPF_JEQ => 0x84, // je
+                    BPF_JGT => 0x87, // ja
+                    BPF_JGE => 0x83, // jae
+                    _ => 0x85,       // jne
+                };
+                code.extend_from_slice(&[0x0f, condition]);
+                fixups.push((code.len(), Target::Instruction(next + insn.jt as usize)));
+                code.extend_from_slice(&[0; 4]);
+                code.push(0xe9); // jmp
+                fixups.push((code.len(), Target::Instruction(next + insn.jf as usize)));
+                code.extend_from_slice(&[0; 4]);
+            }
+            (BPF_RET, _) => {
+                if rval(insn.code) == BPF_K {
+                    code.push(0xb8); // mov eax, k
+                    code.extend_from_slice(&k.to_le_bytes());
+                }
+                code.extend_from_slice(&[0x48, 0x83, 0xc4, 0x40, 0xc3]); // add rsp, 64; ret
+            }
+            (BPF_MISC, _) => match miscop(insn.code) {
+                BPF_TAX => code.extend_from_slice(&[0x41, 0x89, 0xc0]), // mov r8d, eax
+                _ => code.extend_from_slice(&[0x44, 0x89, 0xc0]),       // mov eax, r8d
+            },
+            _ => unreachable!("instruction {} passed the check", pc),
+        }
+    }
+
+    let abort = code.len();
+    code.extend_from_slice(&[0x31, 0xc0, 0x48, 0x83, 0xc4, 0x40, 0xc3]); // xor eax, eax; add rsp, 64; ret
+
+    for (offset, target) in fixups {
+        let target = match target {
+            Target::Instruction(pc) => starts[pc],
+            Target::Abort => abort,
+        };
+        let relative = target as i64 - (offset as i64 + 4);
+        code[offset..offset + 4].copy_from_slice(&(relative as i32).to_le_bytes());
+    }
+
+    code
+}
+
+/// Emits a jump to the abort code if `width` bytes at offset `r9` are not all within the packet
+fn bounds(code: &mut Vec<u8>, fixups: &mut Vec<(usize, Target)>, width: u8) {
+    code.extend_from_slice(&[0x4d, 0x8d, 0x51, width]); // lea r10, [r9 + width]
+    code.extend_from_slice(&[0x49, 0x39, 0xf2]); // cmp r10, rsi
+    code.extend_from_slice(&[0x0f, 0x87]); // ja abort
+    fixups.push((code.len(), Target::Abort));
+    code.extend_from_slice(&[0; 4]);
+}
+
+fn alu(code: &mut Vec<u8>, fixups: &mut Vec<(usize, Target)>, insn: &Instruction) {
+    let k = insn.k.to_le_bytes();
+
+    if src(insn.code) == BPF_X {
+        match op(insn.code) {
+            BPF_ADD => code.extend_from_slice(&[0x44, 0x01, 0xc0]), // add eax, r8d
+            BPF_SUB => code.extend_from_slice(&[0x44, 0x29, 0xc0]), // sub eax, r8d
+            BPF_MUL => code.extend_from_slice(&[0x41, 0x0f, 0xaf, 0xc0]), // imul eax, r8d
+            BPF_OR => code.extend_from_slice(&[0x44, 0x09, 0xc0]),  // or eax, r8d
+            BPF_AND => code.extend_from_slice(&[0x44, 0x21, 0xc0]), // and eax, r8d
+            BPF_XOR => code.extend_from_slice(&[0x44, 0x31, 0xc0]), // xor eax, r8d
+            BPF_LSH => code.extend_from_slice(&[0x44, 0x89, 0xc1, 0xd3, 0xe0]), // mov ecx, r8d; shl eax, cl
+            BPF_RSH => code.extend_from_slice(&[0x44, 0x89, 0xc1, 0xd3, 0xe8]), // mov ecx, r8d; shr eax, cl
+            _ => {
+                code.extend_from_slice(&[0x45, 0x85, 0xc0]); // test r8d, r8d
+                code.extend_from_slice(&[0x0f, 0x84]); // jz abort
+                fixups.push((code.len(), Target::Abort));
+                code.extend_from_slice(&[0; 4]);
+                code.extend_from_slice(&[0x31, 0xd2, 0x41, 0xf7, 0xf0]); // xor edx, edx; div r8d
+                if op(insn.code) == BPF_MOD {
+                    code.extend_from_slice(&[0x89, 0xd0]); // mov eax, edx
+                }
+            }
+        }
+        return;
+    }
+
+    match op(insn.code) {
+        BPF_ADD => code.push(0x05),                                    // add eax, k
+        BPF_SUB => code.push(0x2d),                                    // sub eax, k
+        BPF_MUL => code.extend_from_slice(&[0x69, 0xc0]),              // imul eax, eax, k
+        BPF_OR => code.push(0x0d),                                     // or eax, k
+        BPF_AND => code.push(0x25),                                    // and eax, k
+        BPF_XOR => code.push(0x35),                                    // xor eax, k
+        BPF_LSH => return code.extend_from_slice(&[0xc1, 0xe0, k[0]]), // shl eax, k
+        BPF_RSH => return code.extend_from_slice(&[0xc1, 0xe8, k[0]]), // shr eax, k
+        BPF_NEG => return code.extend_from_slice(&[0xf7, 0xd8]),       // neg eax
+        _ => {
+            code.extend_from_slice(&[0x41, 0xb9]); // mov r9d, k
+            code.extend_from_slice(&k);
+            code.extend_from_slice(&[0x31, 0xd2, 0x41, 0xf7, 0xf1]); // xor edx, edx; div r9d
+            if op(insn.code) == BPF_MOD {
+                code.extend_from_slice(&[0x89, 0xd0]); // mov eax, edx
+            }
+            return;
+        }
+    }
+    code.extend_from_slice(&k);
+}
@@ -2,12 +2,50 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 mod assembler;
-pub use assembler::{assemble, AssemblerError};
+pub use assembler::{
+    assemble, assemble_with_debug_info, assemble_with_options, Assembler, AssemblerError, DebugInfo, Limits, Options,
+    SourceLocation,
+};
+
+pub mod build;
+
+pub mod cfg;
+pub mod codegen;
+
+mod dialect;
+pub use dialect::Dialect;
+
+mod disassembler;
+pub mod ebpf;
+pub mod elf;
 
 pub mod extensions;
 
+pub mod interpreter;
 mod instruction;
 pub use instruction::Instruction;
+#[cfg(all(feature = "jit", target_arch = "x86_64", unix))]
+pub mod jit;
+
+mod listing;
+pub use listing::listing;
+#[cfg(feature = "serde")]
+pub mod mnemonic;
+
+mod opcodes;
+
+mod optimizer;
+pub use optimizer::{optimize, optimize_global};
+
+pub mod pcap;
 
 mod parser;
 use parser::{Parser, Rule};
+
+mod preprocessor;
+
+mod profile;
+pub use profile::Profile;
+
+mod resolver;
+pub use resolver::{FileResolver, Source, SourceResolver};
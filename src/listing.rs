@@ -0,0 +1,63 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fmt::Write;
+
+use crate::assembler::*;
+use crate::extensions::ExtensionResolver;
+use crate::opcodes::*;
+
+/// Assembles `source` like `assemble_with_options` and returns a listing of the result. Each instruction is shown
+/// with its program counter, its encoding in hex and in decimal, the absolute program counters it can jump to, and
+/// the source line it was assembled from, including any comment. Instructions produced by the same statement share
+/// one source line.
+///
+/// ```text
+///   pc  code jt jf k         code jt jf k       targets        source
+///    0  0028 00 00 0000000c  40 0 0 12                         1: ldh [12]           ; ethertype
+///    1  0015 00 01 00000800  21 0 1 2048        jt 2 jf 3      2: jne #0x800, drop
+/// ```
+pub fn listing<S: AsRef<str>, E: ExtensionResolver + ?Sized>(
+    source: S,
+    extensions: &E,
+    options: &Options,
+) -> Result<String, AssemblerError> {
+    let source = source.as_ref();
+    let program = assemble_program(source, &extensions, options)?;
+
+    let mut listing = String::new();
+    writeln!(listing, "{:>4}  {:<19}  {:<18} {:<14} source", "pc", "code jt jf k", "code jt jf k", "targets").unwrap();
+
+    let mut previous = None;
+    for (pc, (insn, location)) in program.instructions.iter().zip(&program.debug_info.locations).enumerate() {
+        let encoding = format!("{:04x} {:02x} {:02x} {:08x}", insn.code, insn.jt, insn.jf, insn.k);
+
+        let next = pc + 1;
+        let targets = match class(insn.code) {
+            BPF_JMP if op(insn.code) == BPF_JA => format!("ja {}", next + insn.k as usize),
+            BPF_JMP => format!("jt {} jf {}", next + insn.jt as usize, next + insn.jf as usize),
+            _ => String::new(),
+        };
+
+        let text = match previous == Some(location) {
+            true => String::new(),
+            false => {
+                let text = match &location.path {
+                    Some(path) => program.sources.get(path).map(String::as_str).unwrap_or_default(),
+                    None => source,
+                };
+                let line = text.lines().nth(location.line - 1).unwrap_or_default().trim_end();
+                match &location.path {
+                    Some(path) => format!("{}:{}: {}", path, location.line, line),
+                    None => format!("{}: {}", location.line, line),
+                }
+            }
+        };
+        previous = Some(location);
+
+        let line = format!("{:>4}  {:<19}  {:<18} {:<14} {}", pc, encoding, insn.to_string(), targets, text);
+        writeln!(listing, "{}", line.trim_end()).unwrap();
+    }
+
+    Ok(listing)
+}
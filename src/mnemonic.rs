@@ -0,0 +1,150 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Human-readable serde representation of instructions, as their assembler mnemonic, for use with
+//! `#[serde(with = "bpfasm::mnemonic")]` on an `Instruction` or `#[serde(with = "bpfasm::mnemonic::program")]` on a
+//! `Vec<Instruction>`
+//!
+//! Jump targets are written as the number of instructions skipped, so `jeq #0x800, 0, 3` continues with the next
+//! instruction if A is 0x800 and skips three otherwise. Instructions that have no mnemonic are written as
+//! `unknown code jt jf k`.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::disassembler::disassemble;
+use crate::opcodes::*;
+use crate::*;
+
+/// Serializes `insn` as its mnemonic
+pub fn serialize<S: Serializer>(insn: &Instruction, serializer: S) -> Result<S::Ok, S::Error> {
+    text(insn).serialize(serializer)
+}
+
+/// Deserializes an instruction from its mnemonic
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instruction, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    parse(&text).ok_or_else(|| D::Error::custom(format!("invalid instruction {:?}", text)))
+}
+
+/// Serializes a program as a sequence of mnemonics
+pub mod program {
+    use super::*;
+
+    /// Serializes `insns` as a sequence of mnemonics
+    pub fn serialize<S: Serializer>(insns: &[Instruction], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(insns.iter().map(text))
+    }
+
+    /// Deserializes a program from a sequence of mnemonics
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Instruction>, D::Error> {
+        let texts = Vec::<String>::deserialize(deserializer)?;
+        let insns = texts.iter().map(|text| parse(text).ok_or_else(|| format!("invalid instruction {:?}", text)));
+        insns.collect::<Result<_, _>>().map_err(D::Error::custom)
+    }
+}
+
+/// Renders `insn` so that `parse` returns it unchanged
+fn text(insn: &Instruction) -> String {
+    let text = disassemble(insn, 0, &|target| (target - 1).to_string());
+    match parse(&text) {
+        Some(parsed) if parsed == *insn => text,
+        _ => format!("unknown {}", insn),
+    }
+}
+
+/// Parses the mnemonic of an instruction, as rendered by `text`
+fn parse(text: &str) -> Option<Instruction> {
+    let (mnemonic, operands) = text.trim().split_once(' ').unwrap_or((text.trim(), ""));
+    let operands = operands.trim();
+    let insn = |code: u16, k: u32| Some(Instruction { code, jt: 0, jf: 0, k });
+
+    let size = match mnemonic {
+        "ldh" => BPF_H,
+        "ldb" => BPF_B,
+        _ => BPF_W,
+    };
+    let alu = match mnemonic {
+        "add" => Some(BPF_ADD),
+        "sub" => Some(BPF_SUB),
+        "mul" => Some(BPF_MUL),
+        "div" => Some(BPF_DIV),
+        "mod" => Some(BPF_MOD),
+        "or" => Some(BPF_OR),
+        "and" => Some(BPF_AND),
+        "xor" => Some(BPF_XOR),
+        "lsh" => Some(BPF_LSH),
+        "rsh" => Some(BPF_RSH),
+        _ => None,
+    };
+    let jump = match mnemonic {
+        "jeq" => Some(BPF_JEQ),
+        "jgt" => Some(BPF_JGT),
+        "jge" => Some(BPF_JGE),
+        "jset" => Some(BPF_JSET),
+        _ => None,
+    };
+
+    match (mnemonic, operands) {
+        ("ld", "len") => insn(BPF_LD | BPF_W | BPF_LEN, 0),
+        ("ld", "rnd") => insn(BPF_LD | BPF_W | BPF_RND, 0),
+        ("ldx", "len") => insn(BPF_LDX | BPF_W | BPF_LEN, 0),
+        ("ld", _) if operands.starts_with('#') => insn(BPF_LD | BPF_W | BPF_IMM, immediate(operands)?),
+        ("ldx", _) if operands.starts_with('#') => insn(BPF_LDX | BPF_W | BPF_IMM, immediate(operands)?),
+        ("ld", _) if operands.starts_with("M[") => insn(BPF_LD | BPF_W | BPF_MEM, memory(operands)?),
+        ("ldx", _) if operands.starts_with("M[") => insn(BPF_LDX | BPF_W | BPF_MEM, memory(operands)?),
+        ("st", _) => insn(BPF_ST, memory(operands)?),
+        ("stx", _) => insn(BPF_STX, memory(operands)?),
+        ("ld" | "ldh" | "ldb", _) => {
+            let offset = operands.strip_prefix('[')?.strip_suffix(']')?;
+            match offset.strip_prefix("x + ") {
+                Some(offset) => insn(BPF_LD | size | BPF_IND, number(offset)?),
+                None => insn(BPF_LD | size | BPF_ABS, number(offset)?),
+            }
+        }
+        ("ldxb", _) => {
+            let offset = operands.strip_prefix("4 * ([")?.strip_suffix("] & 0xf)")?;
+            insn(BPF_LDX | BPF_B | BPF_MSH, number(offset)?)
+        }
+        ("neg", "") => insn(BPF_ALU | BPF_NEG, 0),
+        (_, "x") if alu.is_some() => insn(BPF_ALU | alu? | BPF_X, 0),
+        (_, _) if alu.is_some() => insn(BPF_ALU | alu? | BPF_K, immediate(operands)?),
+        ("ja", _) => insn(BPF_JMP | BPF_JA, number(operands)?),
+        (_, _) if jump.is_some() => {
+            let mut operands = operands.split(',').map(str::trim);
+            let (operand, jt, jf) = (operands.next()?, operands.next()?, operands.next()?);
+            if operands.next().is_some() {
+                return None;
+            }
+            let (src, k) = match operand {
+                "x" => (BPF_X, 0),
+                operand => (BPF_K, immediate(operand)?),
+            };
+            Some(Instruction { code: BPF_JMP | jump? | src, jt: jt.parse().ok()?, jf: jf.parse().ok()?, k })
+        }
+        ("ret", "a") => insn(BPF_RET | BPF_A, 0),
+        ("ret", "x") => insn(BPF_RET | BPF_X, 0),
+        ("ret", _) => insn(BPF_RET | BPF_K, immediate(operands)?),
+        ("tax", "") => insn(BPF_MISC | BPF_TAX, 0),
+        ("txa", "") => insn(BPF_MISC | BPF_TXA, 0),
+        ("cop", _) => insn(BPF_MISC | BPF_COP, immediate(operands)?),
+        ("copx", "") => insn(BPF_MISC | BPF_COPX, 0),
+        ("unknown", _) => operands.parse().ok(),
+        _ => None,
+    }
+}
+
+fn immediate(text: &str) -> Option<u32> {
+    number(text.strip_prefix('#')?)
+}
+
+fn memory(text: &str) -> Option<u32> {
+    number(text.strip_prefix("M[")?.strip_suffix(']')?)
+}
+
+fn number(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
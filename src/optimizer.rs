@@ -0,0 +1,623 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections;
+
+use crate::opcodes::*;
+use crate::*;
+
+/// An instruction whose jump offsets have been resolved to absolute program counters. Instructions that do not jump
+/// have both targets set to the next instruction, and `ja` has both targets set to its destination.
+#[derive(Clone, Copy)]
+struct Node {
+    insn: Instruction,
+    jt: usize,
+    jf: usize,
+}
+
+impl Node {
+    fn is_ja(&self) -> bool {
+        self.insn.code == BPF_JMP | BPF_JA
+    }
+
+    fn is_conditional(&self) -> bool {
+        class(self.insn.code) == BPF_JMP && !self.is_ja()
+    }
+
+    fn is_ret(&self) -> bool {
+        class(self.insn.code) == BPF_RET
+    }
+
+    fn successors(&self) -> impl Iterator<Item = usize> {
+        let count = match (self.is_ret(), self.is_conditional()) {
+            (true, _) => 0,
+            (false, false) => 1,
+            (false, true) => 2,
+        };
+        [self.jt, self.jf].into_iter().take(count)
+    }
+}
+
+/// Identifies a value that a load instruction leaves in the accumulator
+type Value = (u16, u32);
+
+/// Rewrites an assembled program into a shorter equivalent one. Chains of jumps are threaded to their final target,
+/// `ja` to a `ret` becomes that `ret`, jumps to a `ret` are redirected to the last identical `ret`, loads of a value
+/// that the accumulator already holds are dropped, and unreachable instructions are removed. Jump offsets are
+/// recomputed after every rewrite.
+///
+/// Programs containing jumps that leave the program are left untouched.
+pub fn optimize(instructions: &mut Vec<Instruction>) {
+    let mut nodes = match resolve(instructions) {
+        Some(nodes) => nodes,
+        None => return,
+    };
+
+    loop {
+        let mut changed = thread(&mut nodes);
+
+        let mut removed = unreachable(&nodes);
+        redundant(&nodes, &mut removed);
+
+        if removed.iter().any(|removed| *removed) {
+            nodes = compact(nodes, &removed);
+            changed = true;
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    *instructions = nodes.iter().enumerate().map(|(pc, node)| encode(pc, node)).collect();
+}
+
+fn resolve(instructions: &[Instruction]) -> Option<Vec<Node>> {
+    let mut nodes = Vec::with_capacity(instructions.len());
+
+    for (pc, insn) in instructions.iter().enumerate() {
+        let next = pc + 1;
+        let (jt, jf) = if insn.code == BPF_JMP | BPF_JA {
+            let target = next.checked_add(insn.k as usize)?;
+            (target, target)
+        } else if class(insn.code) == BPF_JMP {
+            (next + insn.jt as usize, next + insn.jf as usize)
+        } else {
+            (next, next)
+        };
+        if class(insn.code) != BPF_RET && (jt >= instructions.len() || jf >= instructions.len()) {
+            return None;
+        }
+        nodes.push(Node { insn: *insn, jt, jf });
+    }
+
+    Some(nodes)
+}
+
+fn encode(pc: usize, node: &Node) -> Instruction {
+    let mut insn = node.insn;
+    if node.is_ja() {
+        insn.k = (node.jt - pc - 1) as u32;
+    } else if node.is_conditional() {
+        insn.jt = (node.jt - pc - 1) as u8;
+        insn.jf = (node.jf - pc - 1) as u8;
+    }
+    insn
+}
+
+/// Follows a jump from `pc` along one of its edges to the furthest target that behaves identically
+fn follow(
+    nodes: &[Node],
+    pc: usize,
+    mut target: usize,
+    taken: bool,
+    last: &collections::HashMap<Value, usize>,
+) -> usize {
+    let fits = |target: usize| !nodes[pc].is_conditional() || target - pc - 1 <= u8::MAX as usize;
+
+    loop {
+        let node = &nodes[target];
+        let next = if node.is_ja() {
+            node.jt
+        } else if node.is_conditional() && node.insn.code == nodes[pc].insn.code && node.insn.k == nodes[pc].insn.k {
+            // the accumulator and index register are unchanged, so the same test has the same outcome
+            match taken {
+                true => node.jt,
+                false => node.jf,
+            }
+        } else if node.is_ret() {
+            last[&(node.insn.code, node.insn.k)]
+        } else {
+            target
+        };
+        if next == target || !fits(next) {
+            return target;
+        }
+        target = next;
+    }
+}
+
+fn thread(nodes: &mut [Node]) -> bool {
+    let mut last = collections::HashMap::default();
+    for (pc, node) in nodes.iter().enumerate() {
+        if node.is_ret() {
+            last.insert((node.insn.code, node.insn.k), pc);
+        }
+    }
+
+    let mut changed = false;
+
+    for pc in 0..nodes.len() {
+        let node = nodes[pc];
+        if node.is_ja() {
+            let target = follow(nodes, pc, node.jt, true, &last);
+            if nodes[target].is_ret() {
+                nodes[pc] = Node { insn: nodes[target].insn, jt: pc + 1, jf: pc + 1 };
+                changed = true;
+            } else if target != node.jt {
+                nodes[pc].jt = target;
+                nodes[pc].jf = target;
+                changed = true;
+            }
+        } else if node.is_conditional() {
+            let jt = follow(nodes, pc, node.jt, true, &last);
+            let jf = follow(nodes, pc, node.jf, false, &last);
+            if jt == jf {
+                let insn = Instruction { code: BPF_JMP | BPF_JA, jt: 0, jf: 0, k: 0 };
+                nodes[pc] = Node { insn, jt, jf };
+                changed = true;
+            } else if (jt, jf) != (node.jt, node.jf) {
+                nodes[pc].jt = jt;
+                nodes[pc].jf = jf;
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
+/// Marks instructions that cannot be reached from the entry point, and jumps to the next instruction
+fn unreachable(nodes: &[Node]) -> Vec<bool> {
+    let mut reached = vec![false; nodes.len()];
+    if let Some(entry) = reached.first_mut() {
+        *entry = true;
+    }
+
+    // jumps only go forward, so every predecessor is visited first
+    for pc in 0..nodes.len() {
+        if reached[pc] {
+            let node = &nodes[pc];
+            for successor in node.successors() {
+                reached[successor] = true;
+            }
+        }
+    }
+
+    nodes.iter().enumerate().map(|(pc, node)| !reached[pc] || (node.is_ja() && node.jt == pc + 1)).collect()
+}
+
+/// Marks loads of a value that the accumulator is known to hold on every path that reaches them
+fn redundant(nodes: &[Node], removed: &mut [bool]) {
+    let mut known: Vec<Option<Vec<Value>>> = vec![None; nodes.len()];
+    if let Some(entry) = known.first_mut() {
+        *entry = Some(Vec::default());
+    }
+
+    for pc in 0..nodes.len() {
+        let mut values = match known[pc].take() {
+            Some(values) => values,
+            None => continue,
+        };
+
+        let node = &nodes[pc];
+        let code = node.insn.code;
+        let k = node.insn.k;
+        match class(code) {
+            BPF_LD if values.contains(&(code, k)) => removed[pc] = true,
+            BPF_LD => {
+                values.clear();
+                // random loads and loads from the ancillary data area may return a different value each time
+                if !(mode(code) == BPF_ABS && k >= SKF_AD_OFF || mode(code) == BPF_RND) {
+                    values.push((code, k));
+                }
+            }
+            BPF_LDX => values.retain(|(code, _)| mode(*code) != BPF_IND),
+            BPF_ST => {
+                values.retain(|value| *value != (BPF_LD | BPF_MEM, k));
+                values.push((BPF_LD | BPF_MEM, k));
+            }
+            BPF_STX => values.retain(|value| *value != (BPF_LD | BPF_MEM, k)),
+            BPF_MISC if miscop(code) == BPF_TAX => values.retain(|(code, _)| mode(*code) != BPF_IND),
+            BPF_ALU | BPF_MISC => values.clear(),
+            _ => {}
+        }
+
+        for successor in node.successors() {
+            let successor = &mut known[successor];
+            *successor = match successor.take() {
+                None => Some(values.clone()),
+                Some(mut other) => {
+                    other.retain(|value| values.contains(value));
+                    Some(other)
+                }
+            };
+        }
+    }
+}
+
+fn compact(nodes: Vec<Node>, removed: &[bool]) -> Vec<Node> {
+    // a removed instruction is either unreachable or does nothing, so a jump to it continues at the next survivor
+    let mut survivors = Vec::with_capacity(nodes.len() + 1);
+    let mut count = 0;
+    for removed in removed {
+        survivors.push(count);
+        if !removed {
+            count += 1;
+        }
+    }
+    survivors.push(count);
+
+    nodes
+        .into_iter()
+        .zip(removed)
+        .filter(|(_, removed)| !**removed)
+        .map(|(node, _)| Node { insn: node.insn, jt: survivors[node.jt], jf: survivors[node.jf] })
+        .collect()
+}
+
+/// Rewrites an assembled program like `optimize`, after first running a dataflow analysis over its basic blocks.
+/// Every value held in A, X and M[] is numbered so that identical computations share a number, which allows loads
+/// and stores of a value that is already in place to be removed, and conditional jumps whose outcome is decided by
+/// constants or by an earlier branch on the same value to be replaced with `ja`. Instructions whose results are never
+/// read, such as stores to M[] that are overwritten before any load, are removed as well.
+///
+/// Packet loads are assumed to have no effect other than setting a register, except that a load which can fail is
+/// only removed when the same load has already succeeded on every path.
+pub fn optimize_global(instructions: &mut Vec<Instruction>) {
+    while dataflow(instructions) {
+        optimize(instructions);
+    }
+    optimize(instructions);
+}
+
+type Number = usize;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Expression {
+    Constant(u32),
+    Length,
+    Load(u16, u32, Option<Number>),
+    Alu(u16, Number, Number),
+    Negate(Number),
+    Unknown(usize),
+}
+
+#[derive(Default)]
+struct Numbering {
+    numbers: collections::HashMap<Expression, Number>,
+    constants: Vec<Option<u32>>,
+}
+
+impl Numbering {
+    fn number(&mut self, expression: Expression) -> Number {
+        let constants = &mut self.constants;
+        *self.numbers.entry(expression).or_insert_with(|| {
+            constants.push(match expression {
+                Expression::Constant(k) => Some(k),
+                _ => None,
+            });
+            constants.len() - 1
+        })
+    }
+
+    fn constant(&mut self, k: u32) -> Number {
+        self.number(Expression::Constant(k))
+    }
+
+    fn fresh(&mut self) -> Number {
+        self.number(Expression::Unknown(self.constants.len()))
+    }
+
+    fn value(&self, number: Number) -> Option<u32> {
+        self.constants[number]
+    }
+
+    fn alu(&mut self, code: u16, a: Number, operand: Number) -> Number {
+        let k = self.value(operand);
+        match (op(code), k) {
+            (BPF_ADD | BPF_SUB | BPF_OR | BPF_XOR | BPF_LSH | BPF_RSH, Some(0)) => return a,
+            (BPF_MUL | BPF_DIV, Some(1)) | (BPF_AND, Some(u32::MAX)) => return a,
+            _ => {}
+        }
+
+        let folded = match (self.value(a), k) {
+            (Some(a), Some(k)) => match op(code) {
+                BPF_ADD => Some(a.wrapping_add(k)),
+                BPF_SUB => Some(a.wrapping_sub(k)),
+                BPF_MUL => Some(a.wrapping_mul(k)),
+                BPF_DIV => a.checked_div(k),
+                BPF_MOD => a.checked_rem(k),
+                BPF_OR => Some(a | k),
+                BPF_AND => Some(a & k),
+                BPF_XOR => Some(a ^ k),
+                BPF_LSH => a.checked_shl(k),
+                BPF_RSH => a.checked_shr(k),
+                BPF_NEG => Some(a.wrapping_neg()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match (folded, op(code)) {
+            (Some(k), _) => self.constant(k),
+            (None, BPF_NEG) => self.number(Expression::Negate(a)),
+            // division by a zero X ends the program, so the quotient is never observed
+            (None, _) => self.number(Expression::Alu(op(code), a, operand)),
+        }
+    }
+}
+
+/// The outcome of a conditional jump on one edge
+#[derive(Clone, Copy, PartialEq)]
+struct Fact {
+    a: Number,
+    code: u16,
+    operand: Number,
+    outcome: bool,
+}
+
+#[derive(Clone)]
+struct State {
+    a: Number,
+    x: Number,
+    m: [Number; BPF_MEMWORDS],
+    facts: Vec<Fact>,
+    /// Packet loads that have succeeded on every path, and so cannot end the program when repeated
+    loaded: Vec<Number>,
+}
+
+impl State {
+    fn meet(self, other: State, numbering: &mut Numbering) -> State {
+        let mut meet = |a: Number, b: Number| if a == b { a } else { numbering.fresh() };
+        State {
+            a: meet(self.a, other.a),
+            x: meet(self.x, other.x),
+            m: std::array::from_fn(|k| meet(self.m[k], other.m[k])),
+            facts: self.facts.into_iter().filter(|fact| other.facts.contains(fact)).collect(),
+            loaded: self.loaded.into_iter().filter(|number| other.loaded.contains(number)).collect(),
+        }
+    }
+
+    fn outcome(&self, insn: &Instruction, numbering: &mut Numbering) -> Option<bool> {
+        let operand = match src(insn.code) {
+            BPF_X => self.x,
+            _ => numbering.constant(insn.k),
+        };
+
+        if let Some(fact) =
+            self.facts.iter().find(|fact| fact.a == self.a && fact.code == insn.code && fact.operand == operand)
+        {
+            return Some(fact.outcome);
+        }
+
+        if operand == self.a {
+            match op(insn.code) {
+                BPF_JEQ | BPF_JGE => return Some(true),
+                BPF_JGT => return Some(false),
+                _ => {}
+            }
+        }
+
+        // an earlier `jeq` that was taken tells us the value of A
+        let a = numbering.value(self.a).or_else(|| {
+            self.facts
+                .iter()
+                .filter(|fact| fact.a == self.a && op(fact.code) == BPF_JEQ && fact.outcome)
+                .find_map(|fact| numbering.value(fact.operand))
+        })?;
+        let k = numbering.value(operand)?;
+
+        match op(insn.code) {
+            BPF_JEQ => Some(a == k),
+            BPF_JGT => Some(a > k),
+            BPF_JGE => Some(a >= k),
+            BPF_JSET => Some(a & k != 0),
+            _ => None,
+        }
+    }
+}
+
+/// Runs one round of value numbering and dead store elimination, and reports whether the program changed
+fn dataflow(instructions: &mut Vec<Instruction>) -> bool {
+    let (mut nodes, blocks) = match (resolve(instructions), cfg::blocks(instructions)) {
+        (Some(nodes), Some(blocks)) => (nodes, blocks),
+        _ => return false,
+    };
+    let scratch = |node: &Node| matches!(class(node.insn.code), BPF_ST | BPF_STX) || mode(node.insn.code) == BPF_MEM;
+    if nodes.iter().any(|node| scratch(node) && node.insn.k as usize >= BPF_MEMWORDS) {
+        return false;
+    }
+
+    let mut numbering = Numbering::default();
+    let mut removed = vec![false; nodes.len()];
+    let mut folded = vec![None; nodes.len()];
+    let mut safe = vec![false; nodes.len()];
+
+    let mut states: Vec<Option<State>> = vec![None; blocks.len()];
+    if let Some(entry) = states.first_mut() {
+        *entry = Some(State {
+            a: numbering.fresh(),
+            x: numbering.fresh(),
+            m: std::array::from_fn(|_| numbering.fresh()),
+            facts: Vec::default(),
+            loaded: Vec::default(),
+        });
+    }
+
+    // jumps only go forward, so every predecessor of a block is visited first
+    for (index, block) in blocks.iter().enumerate() {
+        let mut state = match states[index].take() {
+            Some(state) => state,
+            None => continue,
+        };
+
+        for pc in block.start..block.end {
+            let insn = &nodes[pc].insn;
+            let k = insn.k as usize;
+            let value = match class(insn.code) {
+                BPF_LD | BPF_LDX => match mode(insn.code) {
+                    BPF_IMM => numbering.constant(insn.k),
+                    BPF_LEN => numbering.number(Expression::Length),
+                    BPF_MEM => state.m[k],
+                    // loads from the ancillary data area may return a different value each time
+                    BPF_ABS if insn.k >= SKF_AD_OFF => numbering.fresh(),
+                    BPF_ABS | BPF_MSH => numbering.number(Expression::Load(insn.code, insn.k, None)),
+                    BPF_IND => numbering.number(Expression::Load(insn.code, insn.k, Some(state.x))),
+                    _ => numbering.fresh(),
+                },
+                BPF_ST => state.a,
+                BPF_STX => state.x,
+                BPF_ALU => {
+                    let operand = match src(insn.code) {
+                        BPF_X => state.x,
+                        _ => numbering.constant(insn.k),
+                    };
+                    numbering.alu(insn.code, state.a, operand)
+                }
+                BPF_MISC if miscop(insn.code) == BPF_TAX => state.a,
+                BPF_MISC if miscop(insn.code) == BPF_TXA => state.x,
+                BPF_MISC => numbering.fresh(),
+                _ => continue,
+            };
+            let register = match class(insn.code) {
+                BPF_LDX => &mut state.x,
+                BPF_ST | BPF_STX => &mut state.m[k],
+                BPF_MISC if miscop(insn.code) == BPF_TAX => &mut state.x,
+                _ => &mut state.a,
+            };
+            if *register == value {
+                removed[pc] = true;
+            }
+            *register = value;
+
+            if matches!(class(insn.code), BPF_LD | BPF_LDX) && matches!(mode(insn.code), BPF_ABS | BPF_IND | BPF_MSH) {
+                match state.loaded.contains(&value) {
+                    true => safe[pc] = true,
+                    false => state.loaded.push(value),
+                }
+            }
+        }
+
+        let last = block.end - 1;
+        let outcome = match nodes[last].is_conditional() {
+            true => state.outcome(&nodes[last].insn, &mut numbering),
+            false => None,
+        };
+        folded[last] = outcome;
+
+        for edge in &block.edges {
+            let taken = edge.branch != cfg::Branch::False;
+            if outcome.is_some_and(|outcome| outcome != taken) {
+                continue;
+            }
+            let mut state = state.clone();
+            if nodes[last].is_conditional() && outcome.is_none() {
+                let insn = &nodes[last].insn;
+                let operand = match src(insn.code) {
+                    BPF_X => state.x,
+                    _ => numbering.constant(insn.k),
+                };
+                state.facts.push(Fact { a: state.a, code: insn.code, operand, outcome: taken });
+            }
+            states[edge.target] = Some(match states[edge.target].take() {
+                Some(other) => other.meet(state, &mut numbering),
+                None => state,
+            });
+        }
+    }
+
+    for (pc, outcome) in folded.iter().enumerate() {
+        if let Some(outcome) = outcome {
+            let target = if *outcome { nodes[pc].jt } else { nodes[pc].jf };
+            let insn = Instruction { code: BPF_JMP | BPF_JA, jt: 0, jf: 0, k: 0 };
+            nodes[pc] = Node { insn, jt: target, jf: target };
+        }
+    }
+
+    // an instruction is dead if nothing it writes is read before being overwritten on every path, and removing it
+    // cannot change whether the program ends early
+    let mut starts = collections::HashMap::new();
+    for (index, block) in blocks.iter().enumerate() {
+        starts.insert(block.start, index);
+    }
+    let mut live = vec![0u32; blocks.len()];
+    for (index, block) in blocks.iter().enumerate().rev() {
+        let mut registers =
+            nodes[block.end - 1].successors().fold(0, |live_out, target| live_out | live[starts[&target]]);
+
+        for pc in (block.start..block.end).rev() {
+            if removed[pc] {
+                continue;
+            }
+            let (reads, writes, pure) = effects(&nodes[pc].insn, safe[pc]);
+            if writes != 0 && registers & writes == 0 && pure {
+                removed[pc] = true;
+            } else {
+                registers = (registers & !writes) | reads;
+            }
+        }
+
+        live[index] = registers;
+    }
+
+    let changed = removed.iter().any(|removed| *removed) || folded.iter().any(Option::is_some);
+    if changed {
+        *instructions = compact(nodes, &removed).iter().enumerate().map(|(pc, node)| encode(pc, node)).collect();
+    }
+
+    changed
+}
+
+const A: u32 = 1 << BPF_MEMWORDS;
+const X: u32 = 1 << (BPF_MEMWORDS + 1);
+
+/// Returns the registers an instruction reads and writes as bitmasks of M[] slots, A and X, and whether it is free
+/// of any other effect
+fn effects(insn: &Instruction, safe: bool) -> (u32, u32, bool) {
+    let slot = 1u32.checked_shl(insn.k).filter(|slot| *slot < A).unwrap_or(0);
+    let operand = match src(insn.code) {
+        BPF_X => X,
+        _ => 0,
+    };
+    let register = match class(insn.code) {
+        BPF_LD => A,
+        _ => X,
+    };
+    match class(insn.code) {
+        BPF_LD | BPF_LDX => match mode(insn.code) {
+            BPF_IMM | BPF_LEN => (0, register, true),
+            BPF_MEM => (slot, register, true),
+            // some ancillary loads, such as SKF_AD_NLATTR and SKF_AD_ALU_XOR_X, are computed from A and X
+            BPF_ABS if insn.k >= SKF_AD_OFF => (A | X, register, true),
+            BPF_ABS | BPF_MSH => (0, register, safe),
+            BPF_IND => (X, register, safe),
+            _ => (A | X, register, false),
+        },
+        BPF_ST => (A, slot, true),
+        BPF_STX => (X, slot, true),
+        // division by zero ends the program
+        BPF_ALU if matches!(op(insn.code), BPF_DIV | BPF_MOD) => (A | operand, A, operand == 0 && insn.k != 0),
+        BPF_ALU => (A | operand, A, true),
+        BPF_JMP if op(insn.code) == BPF_JA => (0, 0, false),
+        BPF_JMP => (A | operand, 0, false),
+        BPF_RET => match rval(insn.code) {
+            BPF_A => (A, 0, false),
+            BPF_X => (X, 0, false),
+            _ => (0, 0, false),
+        },
+        BPF_MISC if miscop(insn.code) == BPF_TAX => (A, X, true),
+        BPF_MISC if miscop(insn.code) == BPF_TXA => (X, A, true),
+        _ => (A | X, A, false),
+    }
+}
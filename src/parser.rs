@@ -1,2 +1,2 @@
 pub struct Parser;
-# [allow (dead_code , non_camel_case_types)] # [derive (Clone , Copy , Debug , Eq , Hash , Ord , PartialEq , PartialOrd)] pub enum Rule { EOI , WHITESPACE , COMMENT , Hexadecimal , Binary , Octal , Decimal , Integer , Identifier , IndexRegister , PacketOffset , IndirectPacketOffset , MemoryAddress , Immediate , PacketOffsetMSH , Jump , JumpImmediate , JumpIndexRegister , JumpIfImmediate , JumpIfIndexRegister , AccumulatorRegister , Length , Extension , LD , LDI , LDH , LDB , LDX , LDXI , LDXB , ST , STX , JMP , JEQ , JNEQ , JLT , JLE , JGT , JGE , JSET , ADD , SUB , MUL , DIV , MOD , NEG , AND , OR , XOR , LSH , RSH , TAX , TXA , COP , COPX , RET , Label , Instruction , Program } # [allow (clippy :: all)] impl :: pest :: Parser < Rule > for Parser { fn parse < 'i > (rule : Rule , input : & 'i str) -> :: std :: result :: Result < :: pest :: iterators :: Pairs < 'i , Rule > , :: pest :: error :: Error < Rule > > { mod rules { pub mod hidden { use super :: super :: Rule ; # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn skip (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { if state . atomicity () == :: pest :: Atomicity :: NonAtomic { state . sequence (| state | { state . repeat (| state | super :: visible :: WHITESPACE (state)) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: visible :: COMMENT (state) . and_then (| state | { state . repeat (| state | super :: visible :: WHITESPACE (state)) }) }) }) }) }) } else { Ok (state) } } } pub mod visible { use super :: super :: Rule ; # [inline] # [allow (non_snake_case , unused_variables)] pub fn WHITESPACE (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: Atomic , | state | { self :: WHITE_SPACE (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn COMMENT (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("/*") . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . lookahead (false , | state | { state . match_string ("*/") }) . and_then (| state | { self :: ANY (state) }) }) }) }) . and_then (| state | { state . match_string ("*/") }) }) . or_else (| state | { state . sequence (| state | { state . match_string (";") . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . lookahead (false , | state | { self :: NEWLINE (state) }) . and_then (| state | { self :: ANY (state) }) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Hexadecimal (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: Hexadecimal , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("0x") . and_then (| state | { self :: ASCII_HEX_DIGIT (state) }) . and_then (| state | { state . repeat (| state | { self :: ASCII_HEX_DIGIT (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Binary (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: Binary , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("0b") . and_then (| state | { state . match_range ('0' .. '1') }) . and_then (| state | { state . repeat (| state | { state . match_range ('0' .. '1') }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Octal (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: Octal , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("0") . and_then (| state | { self :: ASCII_OCT_DIGIT (state) }) . and_then (| state | { state . repeat (| state | { self :: ASCII_OCT_DIGIT (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Decimal (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: Decimal , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . match_string ("0") . or_else (| state | { state . sequence (| state | { state . optional (| state | { state . match_string ("-") . or_else (| state | { state . match_string ("+") }) }) . and_then (| state | { self :: ASCII_NONZERO_DIGIT (state) }) . and_then (| state | { state . repeat (| state | { self :: ASCII_DIGIT (state) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Integer (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { self :: Hexadecimal (state) . or_else (| state | { self :: Binary (state) }) . or_else (| state | { self :: Octal (state) }) . or_else (| state | { self :: Decimal (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Identifier (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: Identifier , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { self :: ASCII_ALPHA (state) . and_then (| state | { state . repeat (| state | { self :: ASCII_ALPHANUMERIC (state) . or_else (| state | { state . match_string ("_") }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn IndexRegister (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: IndexRegister , | state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn PacketOffset (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: PacketOffset , | state | { state . sequence (| state | { state . match_string ("[") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn IndirectPacketOffset (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: IndirectPacketOffset , | state | { state . sequence (| state | { state . match_string ("[") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("+") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn MemoryAddress (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: MemoryAddress , | state | { state . sequence (| state | { state . match_string ("M") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("[") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Immediate (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: Immediate , | state | { state . sequence (| state | { state . optional (| state | { state . match_string ("#") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Integer (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn PacketOffsetMSH (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: PacketOffsetMSH , | state | { state . sequence (| state | { state . match_string ("4") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("*") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("(") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("[") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("&") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("0xf") . or_else (| state | { state . match_string ("0xF") }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (")") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Jump (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: Jump , | state | { self :: Identifier (state) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JumpImmediate (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: JumpImmediate , | state | { state . sequence (| state | { state . match_string ("#") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Identifier (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JumpIndexRegister (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: JumpIndexRegister , | state | { state . sequence (| state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Identifier (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JumpIfImmediate (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: JumpIfImmediate , | state | { state . sequence (| state | { state . match_string ("#") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JumpIfIndexRegister (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: JumpIfIndexRegister , | state | { state . sequence (| state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn AccumulatorRegister (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: AccumulatorRegister , | state | { state . match_string ("a") . or_else (| state | { state . match_string ("%a") }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Length (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: Length , | state | { state . sequence (| state | { state . optional (| state | { state . match_string ("#") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("len") . or_else (| state | { state . match_string ("pktlen") }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Extension (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: Extension , | state | { state . sequence (| state | { state . optional (| state | { state . match_string ("#") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LD (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LD , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ld") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: PacketOffset (state) . or_else (| state | { self :: IndirectPacketOffset (state) }) . or_else (| state | { self :: MemoryAddress (state) }) . or_else (| state | { self :: Immediate (state) }) . or_else (| state | { self :: Length (state) }) . or_else (| state | { self :: Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LDI (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LDI , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldi") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: Immediate (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LDH (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LDH , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldh") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: PacketOffset (state) . or_else (| state | { self :: IndirectPacketOffset (state) }) . or_else (| state | { self :: Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LDB (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LDB , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldb") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: PacketOffset (state) . or_else (| state | { self :: IndirectPacketOffset (state) }) . or_else (| state | { self :: Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LDX (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LDX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldx") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: MemoryAddress (state) . or_else (| state | { self :: PacketOffsetMSH (state) }) . or_else (| state | { self :: Immediate (state) }) . or_else (| state | { self :: Length (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LDXI (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LDXI , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldxi") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: Immediate (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LDXB (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LDXB , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldxb") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: PacketOffsetMSH (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn ST (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: ST , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("st") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: MemoryAddress (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn STX (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: STX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("stx") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: MemoryAddress (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JMP (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JMP , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jmp") . or_else (| state | { state . match_string ("ja") }) . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: Jump (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JEQ (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JEQ , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jeq") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: JumpIndexRegister (state) . or_else (| state | { self :: JumpIfIndexRegister (state) }) . or_else (| state | { self :: JumpImmediate (state) }) . or_else (| state | { self :: JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JNEQ (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JNEQ , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jneq") . or_else (| state | { state . match_string ("jne") }) . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: JumpIfIndexRegister (state) . or_else (| state | { self :: JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JLT (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JLT , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jlt") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: JumpIfIndexRegister (state) . or_else (| state | { self :: JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JLE (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JLE , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jle") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: JumpIfIndexRegister (state) . or_else (| state | { self :: JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JGT (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JGT , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jgt") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: JumpIndexRegister (state) . or_else (| state | { self :: JumpIfIndexRegister (state) }) . or_else (| state | { self :: JumpImmediate (state) }) . or_else (| state | { self :: JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JGE (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JGE , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jge") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: JumpIndexRegister (state) . or_else (| state | { self :: JumpIfIndexRegister (state) }) . or_else (| state | { self :: JumpImmediate (state) }) . or_else (| state | { self :: JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn JSET (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: JSET , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jset") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: JumpIndexRegister (state) . or_else (| state | { self :: JumpIfIndexRegister (state) }) . or_else (| state | { self :: JumpImmediate (state) }) . or_else (| state | { self :: JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn ADD (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: ADD , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("add") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn SUB (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: SUB , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("sub") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn MUL (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: MUL , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("mul") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn DIV (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: DIV , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("div") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn MOD (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: MOD , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("mod") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn NEG (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: NEG , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . match_string ("neg") }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn AND (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: AND , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("and") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn OR (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: OR , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("or") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn XOR (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: XOR , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("xor") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn LSH (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: LSH , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("lsh") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn RSH (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: RSH , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("rsh") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: IndexRegister (state) . or_else (| state | { self :: Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn TAX (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: TAX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . match_string ("tax") }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn TXA (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: TXA , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . match_string ("txa") }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn COP (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: COP , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("cop") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: Immediate (state) . or_else (| state | { self :: Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn COPX (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: COPX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . match_string ("copx") }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn RET (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: RET , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ret") . and_then (| state | { state . sequence (| state | { self :: WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: Immediate (state) . or_else (| state | { self :: IndexRegister (state) }) . or_else (| state | { self :: AccumulatorRegister (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Label (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: Label , | state | { state . sequence (| state | { self :: Identifier (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (":") }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Instruction (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { self :: LD (state) . or_else (| state | { self :: LDI (state) }) . or_else (| state | { self :: LDH (state) }) . or_else (| state | { self :: LDB (state) }) . or_else (| state | { self :: LDX (state) }) . or_else (| state | { self :: LDXI (state) }) . or_else (| state | { self :: LDXB (state) }) . or_else (| state | { self :: ST (state) }) . or_else (| state | { self :: STX (state) }) . or_else (| state | { self :: JMP (state) }) . or_else (| state | { self :: JEQ (state) }) . or_else (| state | { self :: JNEQ (state) }) . or_else (| state | { self :: JLT (state) }) . or_else (| state | { self :: JLE (state) }) . or_else (| state | { self :: JGT (state) }) . or_else (| state | { self :: JGE (state) }) . or_else (| state | { self :: JSET (state) }) . or_else (| state | { self :: ADD (state) }) . or_else (| state | { self :: SUB (state) }) . or_else (| state | { self :: MUL (state) }) . or_else (| state | { self :: DIV (state) }) . or_else (| state | { self :: MOD (state) }) . or_else (| state | { self :: NEG (state) }) . or_else (| state | { self :: AND (state) }) . or_else (| state | { self :: OR (state) }) . or_else (| state | { self :: XOR (state) }) . or_else (| state | { self :: LSH (state) }) . or_else (| state | { self :: RSH (state) }) . or_else (| state | { self :: TAX (state) }) . or_else (| state | { self :: TXA (state) }) . or_else (| state | { self :: COP (state) }) . or_else (| state | { self :: COPX (state) }) . or_else (| state | { self :: RET (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn Program (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . sequence (| state | { self :: SOI (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . sequence (| state | { state . optional (| state | { self :: Label (state) . or_else (| state | { self :: Instruction (state) }) . or_else (| state | { self :: NEWLINE (state) }) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { self :: Label (state) . or_else (| state | { self :: Instruction (state) }) . or_else (| state | { self :: NEWLINE (state) }) }) }) }) }) }) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: EOI (state) }) }) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ANY (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . skip (1) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn EOI (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . rule (Rule :: EOI , | state | state . end_of_input ()) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn SOI (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . start_of_input () } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_DIGIT (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_range ('0' ..'9') } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_NONZERO_DIGIT (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_range ('1' ..'9') } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_OCT_DIGIT (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_range ('0' ..'7') } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_HEX_DIGIT (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_range ('0' ..'9') . or_else (| state | state . match_range ('a' ..'f')) . or_else (| state | state . match_range ('A' ..'F')) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_ALPHA (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_range ('a' ..'z') . or_else (| state | state . match_range ('A' ..'Z')) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_ALPHANUMERIC (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_range ('a' ..'z') . or_else (| state | state . match_range ('A' ..'Z')) . or_else (| state | state . match_range ('0' ..'9')) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn NEWLINE (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_string ("\n") . or_else (| state | state . match_string ("\r\n")) . or_else (| state | state . match_string ("\r")) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] fn WHITE_SPACE (state : Box < :: pest :: ParserState < Rule >>) -> :: pest :: ParseResult < Box < :: pest :: ParserState < Rule >> > { state . match_char_by (:: pest :: unicode :: WHITE_SPACE) } } pub use self :: visible :: * ; } :: pest :: state (input , | state | { match rule { Rule :: WHITESPACE => rules :: WHITESPACE (state) , Rule :: COMMENT => rules :: COMMENT (state) , Rule :: Hexadecimal => rules :: Hexadecimal (state) , Rule :: Binary => rules :: Binary (state) , Rule :: Octal => rules :: Octal (state) , Rule :: Decimal => rules :: Decimal (state) , Rule :: Integer => rules :: Integer (state) , Rule :: Identifier => rules :: Identifier (state) , Rule :: IndexRegister => rules :: IndexRegister (state) , Rule :: PacketOffset => rules :: PacketOffset (state) , Rule :: IndirectPacketOffset => rules :: IndirectPacketOffset (state) , Rule :: MemoryAddress => rules :: MemoryAddress (state) , Rule :: Immediate => rules :: Immediate (state) , Rule :: PacketOffsetMSH => rules :: PacketOffsetMSH (state) , Rule :: Jump => rules :: Jump (state) , Rule :: JumpImmediate => rules :: JumpImmediate (state) , Rule :: JumpIndexRegister => rules :: JumpIndexRegister (state) , Rule :: JumpIfImmediate => rules :: JumpIfImmediate (state) , Rule :: JumpIfIndexRegister => rules :: JumpIfIndexRegister (state) , Rule :: AccumulatorRegister => rules :: AccumulatorRegister (state) , Rule :: Length => rules :: Length (state) , Rule :: Extension => rules :: Extension (state) , Rule :: LD => rules :: LD (state) , Rule :: LDI => rules :: LDI (state) , Rule :: LDH => rules :: LDH (state) , Rule :: LDB => rules :: LDB (state) , Rule :: LDX => rules :: LDX (state) , Rule :: LDXI => rules :: LDXI (state) , Rule :: LDXB => rules :: LDXB (state) , Rule :: ST => rules :: ST (state) , Rule :: STX => rules :: STX (state) , Rule :: JMP => rules :: JMP (state) , Rule :: JEQ => rules :: JEQ (state) , Rule :: JNEQ => rules :: JNEQ (state) , Rule :: JLT => rules :: JLT (state) , Rule :: JLE => rules :: JLE (state) , Rule :: JGT => rules :: JGT (state) , Rule :: JGE => rules :: JGE (state) , Rule :: JSET => rules :: JSET (state) , Rule :: ADD => rules :: ADD (state) , Rule :: SUB => rules :: SUB (state) , Rule :: MUL => rules :: MUL (state) , Rule :: DIV => rules :: DIV (state) , Rule :: MOD => rules :: MOD (state) , Rule :: NEG => rules :: NEG (state) , Rule :: AND => rules :: AND (state) , Rule :: OR => rules :: OR (state) , Rule :: XOR => rules :: XOR (state) , Rule :: LSH => rules :: LSH (state) , Rule :: RSH => rules :: RSH (state) , Rule :: TAX => rules :: TAX (state) , Rule :: TXA => rules :: TXA (state) , Rule :: COP => rules :: COP (state) , Rule :: COPX => rules :: COPX (state) , Rule :: RET => rules :: RET (state) , Rule :: Label => rules :: Label (state) , Rule :: Instruction => rules :: Instruction (state) , Rule :: Program => rules :: Program (state) , Rule :: EOI => rules :: EOI (state) } }) } }
+# [allow (dead_code , non_camel_case_types , clippy :: upper_case_acronyms)] # [derive (Clone , Copy , Debug , Eq , Hash , Ord , PartialEq , PartialOrd)] pub enum Rule { # [doc = "End-of-input"] EOI , r#WHITESPACE , r#BLANK , r#COMMENT , r#Hexadecimal , r#Binary , r#Octal , r#Decimal , r#Integer , r#IPv4Address , r#PrefixLength , r#Identifier , r#IndexRegister , r#PacketOffset , r#IndirectPacketOffset , r#MemoryAddress , r#Variable , r#Immediate , r#PacketOffsetMSH , r#Jump , r#JumpImmediate , r#JumpIndexRegister , r#JumpIfImmediate , r#JumpIfIndexRegister , r#IPv4Network , r#JumpNetwork , r#AccumulatorRegister , r#Length , r#Extension , r#LD , r#LDI , r#LDH , r#LDB , r#LDX , r#LDXI , r#LDXB , r#ST , r#STX , r#JMP , r#JEQ , r#JNEQ , r#JLT , r#JLE , r#JGT , r#JGE , r#JSET , r#JNET , r#ADD , r#SUB , r#MUL , r#DIV , r#MOD , r#NEG , r#AND , r#OR , r#XOR , r#LSH , r#RSH , r#TAX , r#TXA , r#COP , r#COPX , r#RET , r#Label , r#MacroBody , r#Macro , r#MacroArgument , r#Var , r#IncludePath , r#Include , r#MacroCall , r#Instruction , r#Defined , r#LogicalOr , r#LogicalAnd , r#LogicalNot , r#BitwiseOr , r#BitwiseXor , r#BitwiseAnd , r#BitwiseNot , r#Equal , r#NotEqual , r#LessEqual , r#GreaterEqual , r#ShiftLeft , r#ShiftRight , r#Less , r#Greater , r#Plus , r#Minus , r#Times , r#Divide , r#Modulo , r#Negate , r#PrefixOperator , r#InfixOperator , r#Term , r#Expression , r#Ifdef , r#If , r#Else , r#Conditional , r#Statement , r#Program } impl Rule { pub fn all_rules () -> & 'static [Rule] { & [Rule :: r#WHITESPACE , Rule :: r#BLANK , Rule :: r#COMMENT , Rule :: r#Hexadecimal , Rule :: r#Binary , Rule :: r#Octal , Rule :: r#Decimal , Rule :: r#Integer , Rule :: r#IPv4Address , Rule :: r#PrefixLength , Rule :: r#Identifier , Rule :: r#IndexRegister , Rule :: r#PacketOffset , Rule :: r#IndirectPacketOffset , Rule :: r#MemoryAddress , Rule :: r#Variable , Rule :: r#Immediate , Rule :: r#PacketOffsetMSH , Rule :: r#Jump , Rule :: r#JumpImmediate , Rule :: r#JumpIndexRegister , Rule :: r#JumpIfImmediate , Rule :: r#JumpIfIndexRegister , Rule :: r#IPv4Network , Rule :: r#JumpNetwork , Rule :: r#AccumulatorRegister , Rule :: r#Length , Rule :: r#Extension , Rule :: r#LD , Rule :: r#LDI , Rule :: r#LDH , Rule :: r#LDB , Rule :: r#LDX , Rule :: r#LDXI , Rule :: r#LDXB , Rule :: r#ST , Rule :: r#STX , Rule :: r#JMP , Rule :: r#JEQ , Rule :: r#JNEQ , Rule :: r#JLT , Rule :: r#JLE , Rule :: r#JGT , Rule :: r#JGE , Rule :: r#JSET , Rule :: r#JNET , Rule :: r#ADD , Rule :: r#SUB , Rule :: r#MUL , Rule :: r#DIV , Rule :: r#MOD , Rule :: r#NEG , Rule :: r#AND , Rule :: r#OR , Rule :: r#XOR , Rule :: r#LSH , Rule :: r#RSH , Rule :: r#TAX , Rule :: r#TXA , Rule :: r#COP , Rule :: r#COPX , Rule :: r#RET , Rule :: r#Label , Rule :: r#MacroBody , Rule :: r#Macro , Rule :: r#MacroArgument , Rule :: r#Var , Rule :: r#IncludePath , Rule :: r#Include , Rule :: r#MacroCall , Rule :: r#Instruction , Rule :: r#Defined , Rule :: r#LogicalOr , Rule :: r#LogicalAnd , Rule :: r#LogicalNot , Rule :: r#BitwiseOr , Rule :: r#BitwiseXor , Rule :: r#BitwiseAnd , Rule :: r#BitwiseNot , Rule :: r#Equal , Rule :: r#NotEqual , Rule :: r#LessEqual , Rule :: r#GreaterEqual , Rule :: r#ShiftLeft , Rule :: r#ShiftRight , Rule :: r#Less , Rule :: r#Greater , Rule :: r#Plus , Rule :: r#Minus , Rule :: r#Times , Rule :: r#Divide , Rule :: r#Modulo , Rule :: r#Negate , Rule :: r#PrefixOperator , Rule :: r#InfixOperator , Rule :: r#Term , Rule :: r#Expression , Rule :: r#Ifdef , Rule :: r#If , Rule :: r#Else , Rule :: r#Conditional , Rule :: r#Statement , Rule :: r#Program] } } # [allow (clippy :: all)] impl :: pest :: Parser < Rule > for Parser { fn parse < 'i > (rule : Rule , input : & 'i str) -> :: std :: result :: Result < :: pest :: iterators :: Pairs < 'i , Rule > , :: pest :: error :: Error < Rule > > { mod rules { # ! [allow (clippy :: upper_case_acronyms)] pub mod hidden { use super :: super :: Rule ; # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn skip (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { if state . atomicity () == :: pest :: Atomicity :: NonAtomic { state . sequence (| state | { state . repeat (| state | super :: visible :: WHITESPACE (state)) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: visible :: COMMENT (state) . and_then (| state | { state . repeat (| state | super :: visible :: WHITESPACE (state)) }) }) }) }) }) } else { Ok (state) } } } pub mod visible { use super :: super :: Rule ; # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#WHITESPACE (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: Atomic , | state | { self :: r#WHITE_SPACE (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#BLANK (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { let state = state . check_stack_limit () ? ; state . match_string (" ") . or_else (| state | { state . match_string ("\t") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#COMMENT (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("/*") . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . lookahead (false , | state | { state . match_string ("*/") }) . and_then (| state | { self :: r#ANY (state) }) }) }) }) . and_then (| state | { state . match_string ("*/") }) }) . or_else (| state | { state . sequence (| state | { state . match_string (";") . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . lookahead (false , | state | { self :: r#NEWLINE (state) }) . and_then (| state | { self :: r#ANY (state) }) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Hexadecimal (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Hexadecimal , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("0x") . and_then (| state | { self :: r#ASCII_HEX_DIGIT (state) }) . and_then (| state | { state . repeat (| state | { self :: r#ASCII_HEX_DIGIT (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Binary (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Binary , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("0b") . and_then (| state | { state . match_range ('0' .. '1') }) . and_then (| state | { state . repeat (| state | { state . match_range ('0' .. '1') }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Octal (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Octal , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("0") . and_then (| state | { self :: r#ASCII_OCT_DIGIT (state) }) . and_then (| state | { state . repeat (| state | { self :: r#ASCII_OCT_DIGIT (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Decimal (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Decimal , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . match_string ("0") . or_else (| state | { state . sequence (| state | { state . optional (| state | { state . match_string ("-") . or_else (| state | { state . match_string ("+") }) }) . and_then (| state | { self :: r#ASCII_NONZERO_DIGIT (state) }) . and_then (| state | { state . repeat (| state | { self :: r#ASCII_DIGIT (state) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Integer (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { let state = state . check_stack_limit () ? ; self :: r#Hexadecimal (state) . or_else (| state | { self :: r#Binary (state) }) . or_else (| state | { self :: r#Octal (state) }) . or_else (| state | { self :: r#Decimal (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#IPv4Address (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#IPv4Address , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . sequence (| state | { self :: r#ASCII_DIGIT (state) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) }) . and_then (| state | { state . sequence (| state | { state . match_string (".") . and_then (| state | { self :: r#ASCII_DIGIT (state) }) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) }) }) . and_then (| state | { state . sequence (| state | { state . match_string (".") . and_then (| state | { self :: r#ASCII_DIGIT (state) }) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) }) }) . and_then (| state | { state . match_string (".") }) . and_then (| state | { self :: r#ASCII_DIGIT (state) }) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#PrefixLength (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#PrefixLength , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { self :: r#ASCII_DIGIT (state) . and_then (| state | { state . optional (| state | { self :: r#ASCII_DIGIT (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Identifier (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Identifier , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { self :: r#ASCII_ALPHA (state) . and_then (| state | { state . repeat (| state | { self :: r#ASCII_ALPHANUMERIC (state) . or_else (| state | { state . match_string ("_") }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#IndexRegister (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#IndexRegister , | state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#PacketOffset (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#PacketOffset , | state | { state . sequence (| state | { state . match_string ("[") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#IndirectPacketOffset (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#IndirectPacketOffset , | state | { state . sequence (| state | { state . match_string ("[") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("+") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#MemoryAddress (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#MemoryAddress , | state | { state . sequence (| state | { state . match_string ("M") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("[") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Variable (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#Variable , | state | { self :: r#Identifier (state) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Immediate (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#Immediate , | state | { state . sequence (| state | { state . optional (| state | { state . match_string ("#") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#IPv4Address (state) . or_else (| state | { self :: r#Integer (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#PacketOffsetMSH (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#PacketOffsetMSH , | state | { state . sequence (| state | { state . match_string ("4") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("*") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("(") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("[") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Integer (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("]") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("&") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("0xf") . or_else (| state | { state . match_string ("0xF") }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (")") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Jump (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#Jump , | state | { self :: r#Identifier (state) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JumpImmediate (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#JumpImmediate , | state | { state . sequence (| state | { state . match_string ("#") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#IPv4Address (state) . or_else (| state | { self :: r#Integer (state) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JumpIndexRegister (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#JumpIndexRegister , | state | { state . sequence (| state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JumpIfImmediate (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#JumpIfImmediate , | state | { state . sequence (| state | { state . match_string ("#") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#IPv4Address (state) . or_else (| state | { self :: r#Integer (state) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JumpIfIndexRegister (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#JumpIfIndexRegister , | state | { state . sequence (| state | { state . match_string ("x") . or_else (| state | { state . match_string ("%x") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#IPv4Network (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#IPv4Network , | state | { state . sequence (| state | { self :: r#IPv4Address (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("/") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#PrefixLength (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JumpNetwork (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#JumpNetwork , | state | { state . sequence (| state | { self :: r#PacketOffset (state) . or_else (| state | { self :: r#IndirectPacketOffset (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#IPv4Network (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#AccumulatorRegister (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#AccumulatorRegister , | state | { state . match_string ("a") . or_else (| state | { state . match_string ("%a") }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Length (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#Length , | state | { state . sequence (| state | { state . optional (| state | { state . match_string ("#") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("len") . or_else (| state | { state . match_string ("pktlen") }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Extension (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#Extension , | state | { state . sequence (| state | { state . optional (| state | { state . match_string ("#") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LD (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LD , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ld") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#PacketOffset (state) . or_else (| state | { self :: r#IndirectPacketOffset (state) }) . or_else (| state | { self :: r#MemoryAddress (state) }) . or_else (| state | { self :: r#Immediate (state) }) . or_else (| state | { self :: r#Length (state) }) . or_else (| state | { self :: r#Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LDI (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LDI , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldi") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#Immediate (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LDH (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LDH , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldh") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#PacketOffset (state) . or_else (| state | { self :: r#IndirectPacketOffset (state) }) . or_else (| state | { self :: r#Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LDB (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LDB , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldb") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#PacketOffset (state) . or_else (| state | { self :: r#IndirectPacketOffset (state) }) . or_else (| state | { self :: r#Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LDX (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LDX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldx") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#MemoryAddress (state) . or_else (| state | { self :: r#PacketOffsetMSH (state) }) . or_else (| state | { self :: r#Immediate (state) }) . or_else (| state | { self :: r#Length (state) }) . or_else (| state | { self :: r#Variable (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LDXI (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LDXI , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldxi") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#Immediate (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LDXB (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LDXB , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ldxb") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#PacketOffsetMSH (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#ST (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#ST , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("st") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#MemoryAddress (state) . or_else (| state | { self :: r#Variable (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#STX (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#STX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("stx") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#MemoryAddress (state) . or_else (| state | { self :: r#Variable (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JMP (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JMP , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jmp") . or_else (| state | { state . match_string ("ja") }) . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#Jump (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JEQ (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JEQ , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jeq") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpIndexRegister (state) . or_else (| state | { self :: r#JumpIfIndexRegister (state) }) . or_else (| state | { self :: r#JumpImmediate (state) }) . or_else (| state | { self :: r#JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JNEQ (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JNEQ , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jneq") . or_else (| state | { state . match_string ("jne") }) . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpIfIndexRegister (state) . or_else (| state | { self :: r#JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JLT (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JLT , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jlt") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpIfIndexRegister (state) . or_else (| state | { self :: r#JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JLE (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JLE , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jle") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpIfIndexRegister (state) . or_else (| state | { self :: r#JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JGT (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JGT , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jgt") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpIndexRegister (state) . or_else (| state | { self :: r#JumpIfIndexRegister (state) }) . or_else (| state | { self :: r#JumpImmediate (state) }) . or_else (| state | { self :: r#JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JGE (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JGE , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jge") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpIndexRegister (state) . or_else (| state | { self :: r#JumpIfIndexRegister (state) }) . or_else (| state | { self :: r#JumpImmediate (state) }) . or_else (| state | { self :: r#JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JSET (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JSET , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jset") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpIndexRegister (state) . or_else (| state | { self :: r#JumpIfIndexRegister (state) }) . or_else (| state | { self :: r#JumpImmediate (state) }) . or_else (| state | { self :: r#JumpIfImmediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#JNET (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#JNET , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("jnet") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#JumpNetwork (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#ADD (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#ADD , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("add") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#SUB (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#SUB , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("sub") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#MUL (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#MUL , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("mul") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#DIV (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#DIV , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("div") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#MOD (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#MOD , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("mod") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#NEG (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#NEG , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("neg") . and_then (| state | { state . lookahead (false , | state | { self :: r#ASCII_ALPHANUMERIC (state) . or_else (| state | { state . match_string ("_") }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#AND (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#AND , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("and") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#OR (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#OR , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("or") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#XOR (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#XOR , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("xor") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LSH (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LSH , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("lsh") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#RSH (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#RSH , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("rsh") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#IndexRegister (state) . or_else (| state | { self :: r#Immediate (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#TAX (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#TAX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("tax") . and_then (| state | { state . lookahead (false , | state | { self :: r#ASCII_ALPHANUMERIC (state) . or_else (| state | { state . match_string ("_") }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#TXA (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#TXA , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("txa") . and_then (| state | { state . lookahead (false , | state | { self :: r#ASCII_ALPHANUMERIC (state) . or_else (| state | { state . match_string ("_") }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#COP (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#COP , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("cop") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#Immediate (state) . or_else (| state | { self :: r#Extension (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#COPX (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#COPX , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("copx") . and_then (| state | { state . lookahead (false , | state | { self :: r#ASCII_ALPHANUMERIC (state) . or_else (| state | { state . match_string ("_") }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#RET (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#RET , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . match_string ("ret") . and_then (| state | { state . sequence (| state | { self :: r#WHITE_SPACE (state) . and_then (| state | { state . repeat (| state | { self :: r#WHITE_SPACE (state) }) }) }) }) . and_then (| state | { self :: r#Immediate (state) . or_else (| state | { self :: r#IndexRegister (state) }) . or_else (| state | { self :: r#AccumulatorRegister (state) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Label (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Label , | state | { state . sequence (| state | { self :: r#Identifier (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (":") }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#MacroBody (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#MacroBody , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { let strings = [".endm"] ; state . skip_until (& strings) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Macro (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: CompoundAtomic , | state | { state . rule (Rule :: r#Macro , | state | { state . sequence (| state | { state . match_string (".macro") . and_then (| state | { state . sequence (| state | { self :: r#BLANK (state) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) }) }) . and_then (| state | { self :: r#Identifier (state) }) . and_then (| state | { state . optional (| state | { state . sequence (| state | { state . sequence (| state | { self :: r#BLANK (state) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) }) . and_then (| state | { self :: r#Identifier (state) }) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . repeat (| state | { self :: r#BLANK (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) }) }) }) . and_then (| state | { self :: r#MacroBody (state) }) . and_then (| state | { state . match_string (".endm") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#MacroArgument (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#MacroArgument , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . sequence (| state | { state . lookahead (false , | state | { state . match_string (",") . or_else (| state | { self :: r#NEWLINE (state) }) . or_else (| state | { state . match_string (";") }) . or_else (| state | { state . match_string ("/*") }) }) . and_then (| state | { self :: r#ANY (state) }) }) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . lookahead (false , | state | { state . match_string (",") . or_else (| state | { self :: r#NEWLINE (state) }) . or_else (| state | { state . match_string (";") }) . or_else (| state | { state . match_string ("/*") }) }) . and_then (| state | { self :: r#ANY (state) }) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Var (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: CompoundAtomic , | state | { state . rule (Rule :: r#Var , | state | { state . sequence (| state | { state . match_string (".var") . and_then (| state | { state . sequence (| state | { self :: r#BLANK (state) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) }) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#IncludePath (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#IncludePath , | state | { state . atomic (:: pest :: Atomicity :: Atomic , | state | { state . sequence (| state | { state . sequence (| state | { state . lookahead (false , | state | { state . match_string ("\"") . or_else (| state | { self :: r#NEWLINE (state) }) }) . and_then (| state | { self :: r#ANY (state) }) }) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . lookahead (false , | state | { state . match_string ("\"") . or_else (| state | { self :: r#NEWLINE (state) }) }) . and_then (| state | { self :: r#ANY (state) }) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Include (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: CompoundAtomic , | state | { state . rule (Rule :: r#Include , | state | { state . sequence (| state | { state . match_string (".include") . and_then (| state | { state . sequence (| state | { self :: r#BLANK (state) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) }) }) . and_then (| state | { state . match_string ("\"") }) . and_then (| state | { self :: r#IncludePath (state) }) . and_then (| state | { state . match_string ("\"") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#MacroCall (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: CompoundAtomic , | state | { state . rule (Rule :: r#MacroCall , | state | { state . sequence (| state | { self :: r#Identifier (state) . and_then (| state | { state . optional (| state | { state . sequence (| state | { state . sequence (| state | { self :: r#BLANK (state) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) }) . and_then (| state | { self :: r#MacroArgument (state) }) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { state . repeat (| state | { self :: r#BLANK (state) }) . and_then (| state | { state . match_string (",") }) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) . and_then (| state | { self :: r#MacroArgument (state) }) }) }) }) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Instruction (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { let state = state . check_stack_limit () ? ; self :: r#LD (state) . or_else (| state | { self :: r#LDI (state) }) . or_else (| state | { self :: r#LDH (state) }) . or_else (| state | { self :: r#LDB (state) }) . or_else (| state | { self :: r#LDX (state) }) . or_else (| state | { self :: r#LDXI (state) }) . or_else (| state | { self :: r#LDXB (state) }) . or_else (| state | { self :: r#ST (state) }) . or_else (| state | { self :: r#STX (state) }) . or_else (| state | { self :: r#JMP (state) }) . or_else (| state | { self :: r#JEQ (state) }) . or_else (| state | { self :: r#JNEQ (state) }) . or_else (| state | { self :: r#JLT (state) }) . or_else (| state | { self :: r#JLE (state) }) . or_else (| state | { self :: r#JGT (state) }) . or_else (| state | { self :: r#JGE (state) }) . or_else (| state | { self :: r#JSET (state) }) . or_else (| state | { self :: r#JNET (state) }) . or_else (| state | { self :: r#ADD (state) }) . or_else (| state | { self :: r#SUB (state) }) . or_else (| state | { self :: r#MUL (state) }) . or_else (| state | { self :: r#DIV (state) }) . or_else (| state | { self :: r#MOD (state) }) . or_else (| state | { self :: r#NEG (state) }) . or_else (| state | { self :: r#AND (state) }) . or_else (| state | { self :: r#OR (state) }) . or_else (| state | { self :: r#XOR (state) }) . or_else (| state | { self :: r#LSH (state) }) . or_else (| state | { self :: r#RSH (state) }) . or_else (| state | { self :: r#TAX (state) }) . or_else (| state | { self :: r#TXA (state) }) . or_else (| state | { self :: r#COP (state) }) . or_else (| state | { self :: r#COPX (state) }) . or_else (| state | { self :: r#RET (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Defined (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#Defined , | state | { state . sequence (| state | { state . match_string ("defined") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string ("(") }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Identifier (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (")") }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LogicalOr (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LogicalOr , | state | { state . match_string ("||") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LogicalAnd (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LogicalAnd , | state | { state . match_string ("&&") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LogicalNot (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LogicalNot , | state | { state . match_string ("!") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#BitwiseOr (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#BitwiseOr , | state | { state . match_string ("|") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#BitwiseXor (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#BitwiseXor , | state | { state . match_string ("^") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#BitwiseAnd (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#BitwiseAnd , | state | { state . match_string ("&") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#BitwiseNot (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#BitwiseNot , | state | { state . match_string ("~") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Equal (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Equal , | state | { state . match_string ("==") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#NotEqual (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#NotEqual , | state | { state . match_string ("!=") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#LessEqual (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#LessEqual , | state | { state . match_string ("<=") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#GreaterEqual (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#GreaterEqual , | state | { state . match_string (">=") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#ShiftLeft (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#ShiftLeft , | state | { state . match_string ("<<") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#ShiftRight (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#ShiftRight , | state | { state . match_string (">>") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Less (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Less , | state | { state . match_string ("<") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Greater (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Greater , | state | { state . match_string (">") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Plus (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Plus , | state | { state . match_string ("+") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Minus (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Minus , | state | { state . match_string ("-") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Times (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Times , | state | { state . match_string ("*") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Divide (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Divide , | state | { state . match_string ("/") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Modulo (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Modulo , | state | { state . match_string ("%") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Negate (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Negate , | state | { state . match_string ("-") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#PrefixOperator (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { let state = state . check_stack_limit () ? ; self :: r#LogicalNot (state) . or_else (| state | { self :: r#Negate (state) }) . or_else (| state | { self :: r#BitwiseNot (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#InfixOperator (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { let state = state . check_stack_limit () ? ; self :: r#LogicalOr (state) . or_else (| state | { self :: r#LogicalAnd (state) }) . or_else (| state | { self :: r#BitwiseOr (state) }) . or_else (| state | { self :: r#BitwiseXor (state) }) . or_else (| state | { self :: r#BitwiseAnd (state) }) . or_else (| state | { self :: r#Equal (state) }) . or_else (| state | { self :: r#NotEqual (state) }) . or_else (| state | { self :: r#LessEqual (state) }) . or_else (| state | { self :: r#GreaterEqual (state) }) . or_else (| state | { self :: r#ShiftLeft (state) }) . or_else (| state | { self :: r#ShiftRight (state) }) . or_else (| state | { self :: r#Less (state) }) . or_else (| state | { self :: r#Greater (state) }) . or_else (| state | { self :: r#Plus (state) }) . or_else (| state | { self :: r#Minus (state) }) . or_else (| state | { self :: r#Times (state) }) . or_else (| state | { self :: r#Divide (state) }) . or_else (| state | { self :: r#Modulo (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Term (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { let state = state . check_stack_limit () ? ; self :: r#Defined (state) . or_else (| state | { state . sequence (| state | { state . match_string ("(") . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Expression (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (")") }) }) }) . or_else (| state | { self :: r#Integer (state) }) . or_else (| state | { self :: r#Identifier (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Expression (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: NonAtomic , | state | { state . rule (Rule :: r#Expression , | state | { state . sequence (| state | { state . sequence (| state | { state . optional (| state | { self :: r#PrefixOperator (state) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { self :: r#PrefixOperator (state) }) }) }) }) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Term (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . sequence (| state | { state . optional (| state | { state . sequence (| state | { self :: r#InfixOperator (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . sequence (| state | { state . optional (| state | { self :: r#PrefixOperator (state) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { self :: r#PrefixOperator (state) }) }) }) }) }) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Term (state) }) }) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { state . sequence (| state | { self :: r#InfixOperator (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . sequence (| state | { state . optional (| state | { self :: r#PrefixOperator (state) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { self :: r#PrefixOperator (state) }) }) }) }) }) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#Term (state) }) }) }) }) }) }) }) }) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Ifdef (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: CompoundAtomic , | state | { state . rule (Rule :: r#Ifdef , | state | { state . sequence (| state | { state . match_string (".ifdef") . and_then (| state | { state . sequence (| state | { self :: r#BLANK (state) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) }) }) . and_then (| state | { self :: r#Identifier (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#If (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . atomic (:: pest :: Atomicity :: CompoundAtomic , | state | { state . rule (Rule :: r#If , | state | { state . sequence (| state | { state . match_string (".if") . and_then (| state | { state . lookahead (false , | state | { self :: r#ASCII_ALPHANUMERIC (state) . or_else (| state | { state . match_string ("_") }) }) }) . and_then (| state | { state . repeat (| state | { self :: r#BLANK (state) }) }) . and_then (| state | { self :: r#Expression (state) }) }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Else (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Else , | state | { state . match_string (".else") }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Conditional (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: r#Conditional , | state | { state . sequence (| state | { self :: r#Ifdef (state) . or_else (| state | { self :: r#If (state) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . sequence (| state | { state . optional (| state | { self :: r#Statement (state) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { self :: r#Statement (state) }) }) }) }) }) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . optional (| state | { state . sequence (| state | { self :: r#Else (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . sequence (| state | { state . optional (| state | { self :: r#Statement (state) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { self :: r#Statement (state) }) }) }) }) }) }) }) }) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . match_string (".endif") }) }) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Statement (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { let state = state . check_stack_limit () ? ; self :: r#Label (state) . or_else (| state | { self :: r#Var (state) }) . or_else (| state | { self :: r#Macro (state) }) . or_else (| state | { self :: r#Include (state) }) . or_else (| state | { self :: r#Conditional (state) }) . or_else (| state | { self :: r#Instruction (state) }) . or_else (| state | { self :: r#MacroCall (state) }) . or_else (| state | { self :: r#NEWLINE (state) }) } # [inline] # [allow (non_snake_case , unused_variables)] pub fn r#Program (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . sequence (| state | { self :: r#SOI (state) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { state . sequence (| state | { state . optional (| state | { self :: r#Statement (state) . and_then (| state | { state . repeat (| state | { state . sequence (| state | { super :: hidden :: skip (state) . and_then (| state | { self :: r#Statement (state) }) }) }) }) }) }) }) . and_then (| state | { super :: hidden :: skip (state) }) . and_then (| state | { self :: r#EOI (state) }) }) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ANY (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . skip (1) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn EOI (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . rule (Rule :: EOI , | state | state . end_of_input ()) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn SOI (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . start_of_input () } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_DIGIT (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_range ('0' ..'9') } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_NONZERO_DIGIT (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_range ('1' ..'9') } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_OCT_DIGIT (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_range ('0' ..'7') } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_HEX_DIGIT (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_range ('0' ..'9') . or_else (| state | state . match_range ('a' ..'f')) . or_else (| state | state . match_range ('A' ..'F')) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_ALPHA (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_range ('a' ..'z') . or_else (| state | state . match_range ('A' ..'Z')) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn ASCII_ALPHANUMERIC (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_range ('a' ..'z') . or_else (| state | state . match_range ('A' ..'Z')) . or_else (| state | state . match_range ('0' ..'9')) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] pub fn NEWLINE (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_string ("\n") . or_else (| state | state . match_string ("\r\n")) . or_else (| state | state . match_string ("\r")) } # [inline] # [allow (dead_code , non_snake_case , unused_variables)] fn WHITE_SPACE (state : :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >>) -> :: pest :: ParseResult < :: std :: boxed :: Box < :: pest :: ParserState < '_ , Rule >> > { state . match_char_by (:: pest :: unicode :: WHITE_SPACE) } } pub use self :: visible :: * ; } :: pest :: state (input , | state | { match rule { Rule :: r#WHITESPACE => rules :: r#WHITESPACE (state) , Rule :: r#BLANK => rules :: r#BLANK (state) , Rule :: r#COMMENT => rules :: r#COMMENT (state) , Rule :: r#Hexadecimal => rules :: r#Hexadecimal (state) , Rule :: r#Binary => rules :: r#Binary (state) , Rule :: r#Octal => rules :: r#Octal (state) , Rule :: r#Decimal => rules :: r#Decimal (state) , Rule :: r#Integer => rules :: r#Integer (state) , Rule :: r#IPv4Address => rules :: r#IPv4Address (state) , Rule :: r#PrefixLength => rules :: r#PrefixLength (state) , Rule :: r#Identifier => rules :: r#Identifier (state) , Rule :: r#IndexRegister => rules :: r#IndexRegister (state) , Rule :: r#PacketOffset => rules :: r#PacketOffset (state) , Rule :: r#IndirectPacketOffset => rules :: r#IndirectPacketOffset (state) , Rule :: r#MemoryAddress => rules :: r#MemoryAddress (state) , Rule :: r#Variable => rules :: r#Variable (state) , Rule :: r#Immediate => rules :: r#Immediate (state) , Rule :: r#PacketOffsetMSH => rules :: r#PacketOffsetMSH (state) , Rule :: r#Jump => rules :: r#Jump (state) , Rule :: r#JumpImmediate => rules :: r#JumpImmediate (state) , Rule :: r#JumpIndexRegister => rules :: r#JumpIndexRegister (state) , Rule :: r#JumpIfImmediate => rules :: r#JumpIfImmediate (state) , Rule :: r#JumpIfIndexRegister => rules :: r#JumpIfIndexRegister (state) , Rule :: r#IPv4Network => rules :: r#IPv4Network (state) , Rule :: r#JumpNetwork => rules :: r#JumpNetwork (state) , Rule :: r#AccumulatorRegister => rules :: r#AccumulatorRegister (state) , Rule :: r#Length => rules :: r#Length (state) , Rule :: r#Extension => rules :: r#Extension (state) , Rule :: r#LD => rules :: r#LD (state) , Rule :: r#LDI => rules :: r#LDI (state) , Rule :: r#LDH => rules :: r#LDH (state) , Rule :: r#LDB => rules :: r#LDB (state) , Rule :: r#LDX => rules :: r#LDX (state) , Rule :: r#LDXI => rules :: r#LDXI (state) , Rule :: r#LDXB => rules :: r#LDXB (state) , Rule :: r#ST => rules :: r#ST (state) , Rule :: r#STX => rules :: r#STX (state) , Rule :: r#JMP => rules :: r#JMP (state) , Rule :: r#JEQ => rules :: r#JEQ (state) , Rule :: r#JNEQ => rules :: r#JNEQ (state) , Rule :: r#JLT => rules :: r#JLT (state) , Rule :: r#JLE => rules :: r#JLE (state) , Rule :: r#JGT => rules :: r#JGT (state) , Rule :: r#JGE => rules :: r#JGE (state) , Rule :: r#JSET => rules :: r#JSET (state) , Rule :: r#JNET => rules :: r#JNET (state) , Rule :: r#ADD => rules :: r#ADD (state) , Rule :: r#SUB => rules :: r#SUB (state) , Rule :: r#MUL => rules :: r#MUL (state) , Rule :: r#DIV => rules :: r#DIV (state) , Rule :: r#MOD => rules :: r#MOD (state) , Rule :: r#NEG => rules :: r#NEG (state) , Rule :: r#AND => rules :: r#AND (state) , Rule :: r#OR => rules :: r#OR (state) , Rule :: r#XOR => rules :: r#XOR (state) , Rule :: r#LSH => rules :: r#LSH (state) , Rule :: r#RSH => rules :: r#RSH (state) , Rule :: r#TAX => rules :: r#TAX (state) , Rule :: r#TXA => rules :: r#TXA (state) , Rule :: r#COP => rules :: r#COP (state) , Rule :: r#COPX => rules :: r#COPX (state) , Rule :: r#RET => rules :: r#RET (state) , Rule :: r#Label => rules :: r#Label (state) , Rule :: r#MacroBody => rules :: r#MacroBody (state) , Rule :: r#Macro => rules :: r#Macro (state) , Rule :: r#MacroArgument => rules :: r#MacroArgument (state) , Rule :: r#Var => rules :: r#Var (state) , Rule :: r#IncludePath => rules :: r#IncludePath (state) , Rule :: r#Include => rules :: r#Include (state) , Rule :: r#MacroCall => rules :: r#MacroCall (state) , Rule :: r#Instruction => rules :: r#Instruction (state) , Rule :: r#Defined => rules :: r#Defined (state) , Rule :: r#LogicalOr => rules :: r#LogicalOr (state) , Rule :: r#LogicalAnd => rules :: r#LogicalAnd (state) , Rule :: r#LogicalNot => rules :: r#LogicalNot (state) , Rule :: r#BitwiseOr => rules :: r#BitwiseOr (state) , Rule :: r#BitwiseXor => rules :: r#BitwiseXor (state) , Rule :: r#BitwiseAnd => rules :: r#BitwiseAnd (state) , Rule :: r#BitwiseNot => rules :: r#BitwiseNot (state) , Rule :: r#Equal => rules :: r#Equal (state) , Rule :: r#NotEqual => rules :: r#NotEqual (state) , Rule :: r#LessEqual => rules :: r#LessEqual (state) , Rule :: r#GreaterEqual => rules :: r#GreaterEqual (state) , Rule :: r#ShiftLeft => rules :: r#ShiftLeft (state) , Rule :: r#ShiftRight => rules :: r#ShiftRight (state) , Rule :: r#Less => rules :: r#Less (state) , Rule :: r#Greater => rules :: r#Greater (state) , Rule :: r#Plus => rules :: r#Plus (state) , Rule :: r#Minus => rules :: r#Minus (state) , Rule :: r#Times => rules :: r#Times (state) , Rule :: r#Divide => rules :: r#Divide (state) , Rule :: r#Modulo => rules :: r#Modulo (state) , Rule :: r#Negate => rules :: r#Negate (state) , Rule :: r#PrefixOperator => rules :: r#PrefixOperator (state) , Rule :: r#InfixOperator => rules :: r#InfixOperator (state) , Rule :: r#Term => rules :: r#Term (state) , Rule :: r#Expression => rules :: r#Expression (state) , Rule :: r#Ifdef => rules :: r#Ifdef (state) , Rule :: r#If => rules :: r#If (state) , Rule :: r#Else => rules :: r#Else (state) , Rule :: r#Conditional => rules :: r#Conditional (state) , Rule :: r#Statement => rules :: r#Statement (state) , Rule :: r#Program => rules :: r#Program (state) , Rule :: EOI => rules :: EOI (state) } }) } }
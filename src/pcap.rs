@@ -0,0 +1,444 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Runs programs over the packets in pcap and pcapng capture files
+//!
+//! Each packet is run with `interpreter::run`, or with `jit::Jit` when the `jit` feature is enabled, so `len` is the
+//! number of bytes that were captured rather than the length of the packet on the wire. Accepted packets can be
+//! written to a new pcap file, truncated to the number of bytes that the program returned, as a socket filter would.
+
+use std::io;
+use std::time;
+
+use crate::interpreter::{check, CheckError};
+use crate::*;
+
+const PCAP_MICROSECONDS: u32 = 0xa1b2c3d4;
+const PCAP_NANOSECONDS: u32 = 0xa1b23c4d;
+const PCAPNG_SECTION: u32 = 0x0a0d0d0a;
+const PCAPNG_BYTE_ORDER: u32 = 0x1a2b3c4d;
+const PCAPNG_INTERFACE: u32 = 1;
+const PCAPNG_PACKET: u32 = 2;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+const PCAPNG_TSRESOL: u16 = 9;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LOOP: u32 = 108;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+/// Blocks and packets larger than this are assumed to be corrupt
+const MAX_LENGTH: u32 = 1 << 28;
+/// Snap length of written files, which is the largest that libpcap accepts
+const MAX_SNAPLEN: u32 = 262144;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PcapError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("invalid capture file: {0}")]
+    Format(String),
+    #[error(transparent)]
+    Check(#[from] CheckError),
+    #[error("packet {number} has link type {link_type}, which has no known network layer")]
+    LinkType { number: usize, link_type: u32 },
+    #[error("packet {number} has link type {link_type}, but the output file has link type {output}")]
+    OutputLinkType { number: usize, link_type: u32, output: u32 },
+}
+
+/// Where in each packet the program starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layer {
+    /// The link-layer header, as for `SOCK_RAW` packet sockets and `tcpdump`
+    #[default]
+    Link,
+    /// The network-layer header, as for `SOCK_DGRAM` packet sockets. The link-layer header, including any 802.1Q tags
+    /// of Ethernet frames, is skipped according to the link type of the capture.
+    Network,
+}
+
+/// How packets are run
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub layer: Layer,
+}
+
+/// A packet read from a capture file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// `LINKTYPE_*` value of the interface the packet was captured on
+    pub link_type: u32,
+    /// Time since the Unix epoch at which the packet was captured
+    pub timestamp: time::Duration,
+    /// Length of the packet on the wire, which may be more than was captured
+    pub length: u32,
+    /// The bytes that were captured
+    pub data: Vec<u8>,
+}
+
+/// The result of running a program over one packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    /// Position of the packet in the capture file, starting from 1 like `tcpdump --number`
+    pub number: usize,
+    pub timestamp: time::Duration,
+    /// Length of the packet on the wire
+    pub length: u32,
+    /// Number of bytes the program ran over
+    pub captured: usize,
+    /// The value the program returned, which is the number of bytes to keep, or 0 if the packet was dropped
+    pub result: u32,
+}
+
+/// The results of running a program over a capture file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub accepted: usize,
+    pub dropped: usize,
+    pub packets: Vec<Verdict>,
+}
+
+/// Runs `insns` over every packet in the pcap or pcapng file read from `input`. When `output` is given, accepted
+/// packets are written to it as a pcap file with the link type of the first packet, and accepting a packet with any
+/// other link type is an error.
+pub fn run<R: io::Read>(
+    insns: &[Instruction],
+    input: R,
+    mut output: Option<&mut dyn io::Write>,
+    options: &Options,
+) -> Result<Summary, PcapError> {
+    check(insns)?;
+    #[cfg(all(feature = "jit", target_arch = "x86_64", unix))]
+    let jit = jit::Jit::compile(insns).map_err(|error| match error {
+        jit::JitError::Check(error) => PcapError::Check(error),
+        jit::JitError::Memory(error) => PcapError::Io(error),
+    })?;
+    #[cfg(all(feature = "jit", target_arch = "x86_64", unix))]
+    let filter = |packet: &[u8]| jit.run(packet);
+    #[cfg(not(all(feature = "jit", target_arch = "x86_64", unix)))]
+    let filter = |packet: &[u8]| interpreter::run(insns, packet);
+
+    let mut summary = Summary::default();
+    let mut writer = None;
+
+    for (index, record) in Reader::new(input)?.enumerate() {
+        let record = record?;
+        let number = index + 1;
+
+        let offset = match options.layer {
+            Layer::Link => 0,
+            Layer::Network => network(record.link_type, &record.data)
+                .ok_or(PcapError::LinkType { number, link_type: record.link_type })?,
+        };
+        let packet = record.data.get(offset..).unwrap_or_default();
+        let result = filter(packet);
+
+        match result {
+            0 => summary.dropped += 1,
+            _ => summary.accepted += 1,
+        }
+        summary.packets.push(Verdict {
+            number,
+            timestamp: record.timestamp,
+            length: record.length,
+            captured: packet.len(),
+            result,
+        });
+
+        if let Some(output) = output.take() {
+            writer = Some((Writer::new(output, record.link_type)?, record.link_type));
+        }
+        if let (Some((writer, link_type)), true) = (writer.as_mut(), result != 0) {
+            if record.link_type != *link_type {
+                return Err(PcapError::OutputLinkType { number, link_type: record.link_type, output: *link_type });
+            }
+            let mut record = record;
+            record.data.truncate(offset.saturating_add(result as usize));
+            writer.write(&record)?;
+        }
+    }
+
+    // an empty capture still produces a valid file
+    if let Some(output) = output {
+        Writer::new(output, LINKTYPE_ETHERNET)?;
+    }
+
+    Ok(summary)
+}
+
+/// Returns the offset of the network-layer header in a packet of `link_type`
+fn network(link_type: u32, data: &[u8]) -> Option<usize> {
+    match link_type {
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => Some(0),
+        LINKTYPE_NULL | LINKTYPE_LOOP => Some(4),
+        LINKTYPE_LINUX_SLL => Some(16),
+        LINKTYPE_LINUX_SLL2 => Some(20),
+        LINKTYPE_ETHERNET => {
+            let mut offset = 14;
+            while let Some([0x81, 0x00] | [0x88, 0xa8]) = data.get(offset - 2..offset) {
+                offset += 4;
+            }
+            Some(offset)
+        }
+        _ => None,
+    }
+}
+
+/// Reads the packets of a pcap or pcapng file, which is detected from its first four bytes
+pub struct Reader<R> {
+    input: R,
+    big_endian: bool,
+    format: Format,
+}
+
+enum Format {
+    Pcap {
+        link_type: u32,
+        nanoseconds: bool,
+    },
+    /// The link type and timestamp units per second of each interface in the current section
+    Pcapng {
+        interfaces: Vec<(u32, u64)>,
+    },
+}
+
+impl<R: io::Read> Reader<R> {
+    pub fn new(mut input: R) -> Result<Self, PcapError> {
+        let mut magic = [0u8; 4];
+        if !fill(&mut input, &mut magic)? {
+            return Err(PcapError::Format("the file is empty".to_string()));
+        }
+
+        if u32::from_le_bytes(magic) == PCAPNG_SECTION {
+            let mut length = [0u8; 4];
+            if !fill(&mut input, &mut length)? {
+                return Err(truncated("section header"));
+            }
+            let mut reader = Reader { input, big_endian: false, format: Format::Pcapng { interfaces: Vec::default() } };
+            reader.section(length)?;
+            return Ok(reader);
+        }
+
+        let (big_endian, nanoseconds) = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAP_MICROSECONDS, _) => (false, false),
+            (PCAP_NANOSECONDS, _) => (false, true),
+            (_, PCAP_MICROSECONDS) => (true, false),
+            (_, PCAP_NANOSECONDS) => (true, true),
+            _ => return Err(PcapError::Format(format!("unknown magic number {:#010x}", u32::from_be_bytes(magic)))),
+        };
+        let mut header = [0u8; 20];
+        if !fill(&mut input, &mut header)? {
+            return Err(truncated("file header"));
+        }
+        // the upper bits hold the length of any frame check sequence
+        let network = [header[16], header[17], header[18], header[19]];
+        let network = if big_endian { u32::from_be_bytes(network) } else { u32::from_le_bytes(network) };
+        Ok(Reader { input, big_endian, format: Format::Pcap { link_type: network & 0x0fff_ffff, nanoseconds } })
+    }
+
+    fn pcap(&mut self, link_type: u32, nanoseconds: bool) -> Result<Option<Record>, PcapError> {
+        let mut header = [0u8; 16];
+        if !fill(&mut self.input, &mut header)? {
+            return Ok(None);
+        }
+        let seconds = self.u32(&header[..4]) as u64;
+        let fraction = self.u32(&header[4..8]);
+        let captured = self.u32(&header[8..12]);
+        let length = self.u32(&header[12..16]);
+
+        let nanoseconds = if nanoseconds { fraction } else { fraction.saturating_mul(1000) };
+        let timestamp = time::Duration::from_secs(seconds) + time::Duration::from_nanos(nanoseconds as u64);
+        let data = self.bytes(captured, "packet")?;
+        Ok(Some(Record { link_type, timestamp, length, data }))
+    }
+
+    fn pcapng(&mut self) -> Result<Option<Record>, PcapError> {
+        loop {
+            let mut header = [0u8; 8];
+            if !fill(&mut self.input, &mut header)? {
+                return Ok(None);
+            }
+            if u32::from_le_bytes([header[0], header[1], header[2], header[3]]) == PCAPNG_SECTION {
+                self.section([header[4], header[5], header[6], header[7]])?;
+                continue;
+            }
+
+            let kind = self.u32(&header[..4]);
+            let length = self.u32(&header[4..8]);
+            if length < 12 || !length.is_multiple_of(4) {
+                return Err(PcapError::Format(format!("block of type {:#x} has an invalid length {}", kind, length)));
+            }
+            let body = self.bytes(length - 12, "block")?;
+            self.bytes(4, "block")?;
+
+            let (id, time, captured, length, data) = match kind {
+                PCAPNG_INTERFACE if body.len() >= 8 => {
+                    let link_type = self.u16(&body[..2]) as u32;
+                    let mut units = 1_000_000;
+                    let mut options = &body[8..];
+                    while options.len() >= 4 {
+                        let code = self.u16(&options[..2]);
+                        let size = self.u16(&options[2..4]) as usize;
+                        let value = options.get(4..4 + size).unwrap_or_default();
+                        match (code, value) {
+                            (0, _) => break,
+                            (PCAPNG_TSRESOL, [resolution]) if resolution & 0x80 == 0 => {
+                                units = 10u64.checked_pow(*resolution as u32).unwrap_or(u64::MAX)
+                            }
+                            (PCAPNG_TSRESOL, [resolution]) => {
+                                units = 1u64.checked_shl((resolution & 0x7f) as u32).unwrap_or(u64::MAX)
+                            }
+                            _ => {}
+                        }
+                        options = options.get(4 + size.next_multiple_of(4)..).unwrap_or_default();
+                    }
+                    let Format::Pcapng { interfaces } = &mut self.format else { unreachable!() };
+                    interfaces.push((link_type, units));
+                    continue;
+                }
+                PCAPNG_ENHANCED_PACKET if body.len() >= 20 => {
+                    let time = (self.u32(&body[4..8]) as u64) << 32 | self.u32(&body[8..12]) as u64;
+                    let captured = self.u32(&body[12..16]) as usize;
+                    (self.u32(&body[..4]) as usize, time, captured, self.u32(&body[16..20]), &body[20..])
+                }
+                PCAPNG_PACKET if body.len() >= 20 => {
+                    let time = (self.u32(&body[4..8]) as u64) << 32 | self.u32(&body[8..12]) as u64;
+                    let captured = self.u32(&body[12..16]) as usize;
+                    (self.u16(&body[..2]) as usize, time, captured, self.u32(&body[16..20]), &body[20..])
+                }
+                PCAPNG_SIMPLE_PACKET if body.len() >= 4 => {
+                    let length = self.u32(&body[..4]);
+                    (0, 0, length as usize, length, &body[4..])
+                }
+                PCAPNG_INTERFACE | PCAPNG_ENHANCED_PACKET | PCAPNG_PACKET | PCAPNG_SIMPLE_PACKET => {
+                    return Err(truncated("packet"));
+                }
+                _ => continue,
+            };
+
+            let Format::Pcapng { interfaces } = &self.format else { unreachable!() };
+            let Some(&(link_type, units)) = interfaces.get(id) else {
+                return Err(PcapError::Format(format!("packet refers to undescribed interface {}", id)));
+            };
+            let timestamp =
+                time::Duration::new(time / units, ((time % units) as u128 * 1_000_000_000 / units as u128) as u32);
+            let data = data[..captured.min(data.len())].to_vec();
+            return Ok(Some(Record { link_type, timestamp, length, data }));
+        }
+    }
+
+    /// Reads the rest of a pcapng section header block, after its block type and the undecoded `length`
+    fn section(&mut self, length: [u8; 4]) -> Result<(), PcapError> {
+        let mut magic = [0u8; 4];
+        if !fill(&mut self.input, &mut magic)? {
+            return Err(truncated("section header"));
+        }
+        self.big_endian = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAPNG_BYTE_ORDER, _) => false,
+            (_, PCAPNG_BYTE_ORDER) => true,
+            _ => return Err(PcapError::Format("unknown byte-order magic number".to_string())),
+        };
+        let length = self.u32(&length);
+        if length < 28 || !length.is_multiple_of(4) {
+            return Err(PcapError::Format(format!("section header block has an invalid length {}", length)));
+        }
+        self.bytes(length - 12, "section header")?;
+        self.format = Format::Pcapng { interfaces: Vec::default() };
+        Ok(())
+    }
+
+    fn bytes(&mut self, length: u32, what: &str) -> Result<Vec<u8>, PcapError> {
+        if length > MAX_LENGTH {
+            return Err(PcapError::Format(format!("{} is {} bytes long", what, length)));
+        }
+        let mut bytes = vec![0u8; length as usize];
+        match fill(&mut self.input, &mut bytes)? {
+            true => Ok(bytes),
+            false => Err(truncated(what)),
+        }
+    }
+
+    fn u16(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+}
+
+impl<R: io::Read> Iterator for Reader<R> {
+    type Item = Result<Record, PcapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match self.format {
+            Format::Pcap { link_type, nanoseconds } => self.pcap(link_type, nanoseconds),
+            Format::Pcapng { .. } => self.pcapng(),
+        };
+        result.transpose()
+    }
+}
+
+/// Writes packets as a pcap file with nanosecond timestamps, in the byte order this crate was compiled for
+pub struct Writer<W> {
+    output: W,
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Writes the file header
+    pub fn new(mut output: W, link_type: u32) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(24);
+        header.extend_from_slice(&PCAP_NANOSECONDS.to_ne_bytes());
+        header.extend_from_slice(&2u16.to_ne_bytes());
+        header.extend_from_slice(&4u16.to_ne_bytes());
+        header.extend_from_slice(&[0; 8]);
+        header.extend_from_slice(&MAX_SNAPLEN.to_ne_bytes());
+        header.extend_from_slice(&link_type.to_ne_bytes());
+        output.write_all(&header)?;
+        Ok(Writer { output })
+    }
+
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        let mut header = Vec::with_capacity(16);
+        header.extend_from_slice(&(record.timestamp.as_secs() as u32).to_ne_bytes());
+        header.extend_from_slice(&record.timestamp.subsec_nanos().to_ne_bytes());
+        header.extend_from_slice(&(record.data.len() as u32).to_ne_bytes());
+        header.extend_from_slice(&record.length.to_ne_bytes());
+        self.output.write_all(&header)?;
+        self.output.write_all(&record.data)
+    }
+}
+
+/// Fills `buffer` from `input`. Returns false if `input` ended before the first byte, and an error if it ended after.
+fn fill<R: io::Read>(input: &mut R, buffer: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match input.read(&mut buffer[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(true)
+}
+
+fn truncated(what: &str) -> PcapError {
+    PcapError::Format(format!("the {} is truncated", what))
+}
@@ -0,0 +1,540 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections;
+use std::iter;
+use std::ops;
+
+use pest::iterators::*;
+use pest::pratt_parser::*;
+use pest::*;
+
+use crate::assembler::pair_to_u32;
+use crate::*;
+
+const MNEMONICS: &[&str] = &[
+    "ld", "ldi", "ldh", "ldb", "ldx", "ldxi", "ldxb", "st", "stx", "jmp", "ja", "jeq", "jneq", "jne", "jlt", "jle",
+    "jgt", "jge", "jset", "jnet", "add", "sub", "mul", "div", "mod", "neg", "and", "or", "xor", "lsh", "rsh", "tax",
+    "txa", "cop", "copx", "ret",
+];
+
+struct Macro {
+    parameters: Vec<String>,
+    body: String,
+    path: Option<String>,
+    line: usize,
+    definition: pest::error::Error<Rule>,
+}
+
+impl Macro {
+    /// Substitutes `\parameter` references in the body, and returns the expansion along with where each argument was
+    /// substituted into it. The expansion is padded with newlines so that line numbers in errors inside the expansion
+    /// match the line numbers of the macro definition.
+    fn expand(&self, arguments: &[&str]) -> (String, Vec<(ops::Range<usize>, usize)>) {
+        let mut expansion = "\n".repeat(self.line - 1);
+        let mut substitutions = Vec::default();
+        let mut body = self.body.as_str();
+        while let Some(i) = body.find('\\') {
+            expansion.push_str(&body[..i]);
+            body = &body[i + 1..];
+            let n = body.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(body.len());
+            match self.parameters.iter().position(|parameter| *parameter == body[..n]) {
+                Some(p) => {
+                    substitutions.push((expansion.len()..expansion.len() + arguments[p].len(), p));
+                    expansion.push_str(arguments[p]);
+                }
+                None => {
+                    expansion.push('\\');
+                    expansion.push_str(&body[..n]);
+                }
+            }
+            body = &body[n..];
+        }
+        expansion.push_str(body);
+        (expansion, substitutions)
+    }
+}
+
+/// Labels and variables declared inside a macro expansion are local to that expansion, and are renamed so that they
+/// never clash with names declared elsewhere or by other expansions of the same macro.
+pub(crate) struct Scope<'s> {
+    expansion: usize,
+    locals: collections::HashSet<String>,
+    /// Where names passed as macro arguments were substituted into this expansion, and what they refer to in the
+    /// scope of the call
+    arguments: Vec<(ops::Range<usize>, String)>,
+    parent: Option<&'s Scope<'s>>,
+    path: Option<&'s str>,
+    /// Where the macro call that produced this expansion appears in a source file
+    site: Option<SourceLocation>,
+}
+
+impl Scope<'_> {
+    /// Resolves the label or variable name at `name`, a span within this scope's source text
+    pub(crate) fn resolve(&self, name: Span) -> String {
+        match self.arguments.iter().find(|(range, _)| *range == (name.start()..name.end())) {
+            Some((_, resolved)) => resolved.clone(),
+            None => self.resolve_name(name.as_str()),
+        }
+    }
+
+    /// The macro expansion that the label or variable declared at `name` is local to, if any
+    pub(crate) fn owner(&self, name: Span) -> Option<usize> {
+        let argument = self.arguments.iter().any(|(range, _)| *range == (name.start()..name.end()));
+        (!argument && self.locals.contains(name.as_str())).then_some(self.expansion)
+    }
+
+    /// The macro expansions that statements in this scope are part of, innermost first
+    pub(crate) fn expansions(&self) -> impl Iterator<Item = usize> + '_ {
+        iter::successors(Some(self), |scope| scope.parent)
+            .filter(|scope| scope.site.is_some())
+            .map(|scope| scope.expansion)
+    }
+
+    fn resolve_name(&self, name: &str) -> String {
+        if self.locals.contains(name) {
+            format!("{}@{}", name, self.expansion)
+        } else {
+            match self.parent {
+                Some(parent) => parent.resolve_name(name),
+                None => name.into(),
+            }
+        }
+    }
+
+    /// Maps a span within this scope's source text to a location in a source file. Statements inside a macro
+    /// expansion are located at the outermost call of the macro.
+    pub(crate) fn locate(&self, span: Span) -> SourceLocation {
+        match &self.site {
+            Some(site) => site.clone(),
+            None => {
+                let (line, column) = span.start_pos().line_col();
+                SourceLocation { path: self.path.map(String::from), span: span.start()..span.end(), line, column }
+            }
+        }
+    }
+}
+
+pub(crate) type Visitor<'v> = dyn FnMut(Pair<Rule>, &Scope) -> Result<(), AssemblerError> + 'v;
+
+/// Sources returned by the resolver, by the name in the `.include` directive and the name of the including source.
+/// Every pass of `assemble` shares one, so that each file is read once and every pass sees the same text.
+pub(crate) type Includes = collections::HashMap<(String, Option<String>), Source>;
+
+/// Expands macros and included files and hands each remaining statement to a visitor. Both passes of `assemble` run
+/// a fresh `Preprocessor` over the same source, so they observe the same statements in the same order.
+pub(crate) struct Preprocessor<'a> {
+    options: &'a Options,
+    resolved: &'a mut Includes,
+    macros: collections::HashMap<String, Macro>,
+    expansions: usize,
+    stack: Vec<String>,
+    includes: Vec<String>,
+    /// Text of every included source, by name
+    pub(crate) sources: collections::HashMap<String, String>,
+}
+
+impl<'a> Preprocessor<'a> {
+    pub(crate) fn new(options: &'a Options, resolved: &'a mut Includes) -> Self {
+        Preprocessor {
+            options,
+            resolved,
+            macros: collections::HashMap::default(),
+            expansions: 0,
+            stack: Vec::default(),
+            includes: Vec::default(),
+            sources: collections::HashMap::default(),
+        }
+    }
+
+    pub(crate) fn run(&mut self, source: &str, visit: &mut Visitor) -> Result<(), AssemblerError> {
+        self.walk(source, None, None, None, Vec::default(), visit)
+    }
+
+    /// Errors that originate in `source` are tagged with its path; errors from nested sources are tagged by the
+    /// nested walk.
+    fn walk(
+        &mut self,
+        source: &str,
+        path: Option<&str>,
+        parent: Option<&Scope>,
+        site: Option<SourceLocation>,
+        arguments: Vec<(ops::Range<usize>, String)>,
+        visit: &mut Visitor,
+    ) -> Result<(), AssemblerError> {
+        self.statements(source, path, parent, site, arguments, visit).map_err(|error| match path {
+            Some(path) => error.with_path(path),
+            None => error,
+        })
+    }
+
+    fn statements(
+        &mut self,
+        source: &str,
+        path: Option<&str>,
+        parent: Option<&Scope>,
+        site: Option<SourceLocation>,
+        arguments: Vec<(ops::Range<usize>, String)>,
+        visit: &mut Visitor,
+    ) -> Result<(), AssemblerError> {
+        let pairs = crate::Parser::parse(Rule::Program, source)?;
+
+        let scope = Scope {
+            expansion: self.expansions,
+            // only macro expansions have a call site, and only their labels and variables are local
+            locals: match site.is_some() {
+                true => declarations(pairs.clone(), &self.options.symbols)
+                    .into_iter()
+                    .filter(|name| !arguments.iter().any(|(range, _)| *range == (name.start()..name.end())))
+                    .map(|name| name.as_str().to_string())
+                    .collect(),
+                false => collections::HashSet::default(),
+            },
+            arguments,
+            parent,
+            path,
+            site,
+        };
+
+        for pair in pairs {
+            self.statement(pair, path, &scope, visit)?;
+        }
+
+        Ok(())
+    }
+
+    fn statement(
+        &mut self,
+        pair: Pair<Rule>,
+        path: Option<&str>,
+        scope: &Scope,
+        visit: &mut Visitor,
+    ) -> Result<(), AssemblerError> {
+        match pair.as_rule() {
+            Rule::Macro => self.define(pair, path),
+            Rule::MacroCall => self.expand(pair, scope, visit),
+            Rule::Include => self.include(pair, path, scope, visit),
+            Rule::Conditional => self.conditional(pair, path, scope, visit),
+            Rule::EOI => Ok(()),
+            _ => visit(pair, scope),
+        }
+    }
+
+    /// Statements in the branch that is not taken are skipped entirely, including any macro definitions and
+    /// includes they contain.
+    fn conditional(
+        &mut self,
+        pair: Pair<Rule>,
+        path: Option<&str>,
+        scope: &Scope,
+        visit: &mut Visitor,
+    ) -> Result<(), AssemblerError> {
+        let mut inner_pairs = pair.into_inner();
+
+        let mut enabled = condition(inner_pairs.next().unwrap(), &self.options.symbols)?;
+
+        for pair in inner_pairs {
+            match pair.as_rule() {
+                Rule::Else => enabled = !enabled,
+                _ if enabled => self.statement(pair, path, scope, visit)?,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn define(&mut self, pair: Pair<Rule>, path: Option<&str>) -> Result<(), AssemblerError> {
+        let mut inner_pairs = pair.into_inner();
+        let name = inner_pairs.next().unwrap();
+
+        if MNEMONICS.contains(&name.as_str()) {
+            Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!("macro {:?} shadows an instruction", name.as_str()),
+                },
+                name.as_span(),
+            ))?;
+        }
+        if self.macros.contains_key(name.as_str()) {
+            Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError { message: format!("redeclared macro {:?}", name.as_str()) },
+                name.as_span(),
+            ))?;
+        }
+
+        let mut parameters: Vec<String> = Vec::default();
+        let mut body = None;
+        for pair in inner_pairs {
+            match pair.as_rule() {
+                Rule::Identifier => {
+                    if parameters.iter().any(|parameter| parameter == pair.as_str()) {
+                        Err(pest::error::Error::new_from_span(
+                            pest::error::ErrorVariant::CustomError {
+                                message: format!("redeclared macro parameter {:?}", pair.as_str()),
+                            },
+                            pair.as_span(),
+                        ))?;
+                    }
+                    parameters.push(pair.as_str().into());
+                }
+                Rule::MacroBody => body = Some(pair),
+                _ => unreachable!(),
+            }
+        }
+        let body = body.unwrap();
+
+        self.macros.insert(
+            name.as_str().into(),
+            Macro {
+                parameters,
+                body: body.as_str().into(),
+                path: path.map(String::from),
+                line: body.as_span().start_pos().line_col().0,
+                definition: {
+                    let definition = pest::error::Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: format!("macro {:?} defined here", name.as_str()),
+                        },
+                        name.as_span(),
+                    );
+                    match path {
+                        Some(path) => definition.with_path(path),
+                        None => definition,
+                    }
+                },
+            },
+        );
+
+        Ok(())
+    }
+
+    fn expand(&mut self, pair: Pair<Rule>, scope: &Scope, visit: &mut Visitor) -> Result<(), AssemblerError> {
+        let span = pair.as_span();
+        let mut inner_pairs = pair.into_inner();
+        let name = inner_pairs.next().unwrap();
+        let arguments = inner_pairs.map(|pair| pair.as_span().get(..pair.as_str().trim_end().len()).unwrap());
+        let arguments = arguments.collect::<Vec<_>>();
+
+        let (expansion, path, definition) = match self.macros.get(name.as_str()) {
+            Some(m) if m.parameters.len() != arguments.len() => Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!(
+                        "macro {:?} takes {} argument(s) but {} were supplied",
+                        name.as_str(),
+                        m.parameters.len(),
+                        arguments.len()
+                    ),
+                },
+                span,
+            ))?,
+            Some(m) => {
+                let (expansion, substitutions) =
+                    m.expand(&arguments.iter().map(|argument| argument.as_str()).collect::<Vec<_>>());
+                // arguments that are names are resolved where the macro is called, so that they never refer to a
+                // label or variable that the macro declares itself
+                let substitutions = substitutions
+                    .into_iter()
+                    .filter(|(_, p)| {
+                        let argument = arguments[*p].as_str();
+                        argument.starts_with(|c: char| c.is_ascii_alphabetic())
+                            && argument.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    })
+                    .map(|(range, p)| (range, scope.resolve(arguments[p])))
+                    .collect::<Vec<_>>();
+                ((expansion, substitutions), m.path.clone(), m.definition.clone())
+            }
+            None if MNEMONICS.contains(&name.as_str()) => Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!("invalid operands for instruction {:?}", name.as_str()),
+                },
+                span,
+            ))?,
+            None => Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError { message: format!("undeclared macro {:?}", name.as_str()) },
+                name.as_span(),
+            ))?,
+        };
+
+        if self.stack.iter().any(|m| m == name.as_str()) {
+            Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!("recursive expansion of macro {:?}", name.as_str()),
+                },
+                span,
+            ))?;
+        }
+
+        self.expansions += 1;
+        self.stack.push(name.as_str().into());
+        let site = scope.locate(span);
+        let (expansion, arguments) = expansion;
+        let result = self.walk(&expansion, path.as_deref(), Some(scope), Some(site), arguments, visit);
+        self.stack.pop();
+
+        result.map_err(|error| AssemblerError::Macro {
+            source: Box::new(error),
+            call: Box::new(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!("in expansion of macro {:?}", name.as_str()),
+                },
+                span,
+            )),
+            definition: Box::new(definition),
+        })
+    }
+
+    fn include(
+        &mut self,
+        pair: Pair<Rule>,
+        path: Option<&str>,
+        scope: &Scope,
+        visit: &mut Visitor,
+    ) -> Result<(), AssemblerError> {
+        let span = pair.as_span();
+        let name = pair.into_inner().next().unwrap().as_str();
+
+        let key = (name.to_string(), path.map(String::from));
+        let source = match (self.resolved.get(&key), &self.options.resolver) {
+            (Some(source), _) => source.clone(),
+            (None, Some(resolver)) => resolver.resolve(name, path).map_err(|error| {
+                pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError {
+                        message: format!("could not include {:?}: {}", name, error),
+                    },
+                    span,
+                )
+            })?,
+            (None, None) => Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: format!("could not include {:?}: no source resolver was provided", name),
+                },
+                span,
+            ))?,
+        };
+        self.resolved.entry(key).or_insert_with(|| source.clone());
+
+        if self.includes.contains(&source.name) {
+            Err(pest::error::Error::new_from_span(
+                pest::error::ErrorVariant::CustomError { message: format!("cyclic include of {:?}", source.name) },
+                span,
+            ))?;
+        }
+
+        self.includes.push(source.name.clone());
+        let result = self.walk(&source.text, Some(&source.name), Some(scope), None, Vec::default(), visit);
+        self.includes.pop();
+        self.sources.insert(source.name, source.text);
+
+        result
+    }
+}
+
+/// Whether the first branch of a conditional is taken
+fn condition(condition: Pair<Rule>, symbols: &collections::HashMap<String, u32>) -> Result<bool, AssemblerError> {
+    match condition.as_rule() {
+        Rule::Ifdef => Ok(symbols.contains_key(condition.into_inner().next().unwrap().as_str())),
+        Rule::If => Ok(evaluate(condition.into_inner().next().unwrap(), symbols)? != 0),
+        _ => unreachable!(),
+    }
+}
+
+/// Names of the labels and variables declared by `pairs`, other than in branches of conditionals that are not taken
+fn declarations<'i>(pairs: Pairs<'i, Rule>, symbols: &collections::HashMap<String, u32>) -> Vec<Span<'i>> {
+    let mut names = Vec::default();
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::Label | Rule::Var => names.push(pair.into_inner().next().unwrap().as_span()),
+            Rule::Conditional => {
+                let mut inner_pairs = pair.into_inner();
+                // a condition that cannot be evaluated fails when the conditional is visited
+                let mut enabled = condition(inner_pairs.next().unwrap(), symbols).unwrap_or(false);
+                for pair in inner_pairs {
+                    match pair.as_rule() {
+                        Rule::Else => enabled = !enabled,
+                        _ if enabled => names.extend(declarations(Pairs::single(pair), symbols)),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+fn evaluate(expression: Pair<Rule>, symbols: &collections::HashMap<String, u32>) -> Result<u32, AssemblerError> {
+    let infix = |rules: &[Rule]| rules.iter().map(|rule| Op::infix(*rule, Assoc::Left)).reduce(|a, b| a | b).unwrap();
+    let pratt = PrattParser::new()
+        .op(infix(&[Rule::LogicalOr]))
+        .op(infix(&[Rule::LogicalAnd]))
+        .op(infix(&[Rule::BitwiseOr]))
+        .op(infix(&[Rule::BitwiseXor]))
+        .op(infix(&[Rule::BitwiseAnd]))
+        .op(infix(&[Rule::Equal, Rule::NotEqual]))
+        .op(infix(&[Rule::Less, Rule::LessEqual, Rule::Greater, Rule::GreaterEqual]))
+        .op(infix(&[Rule::ShiftLeft, Rule::ShiftRight]))
+        .op(infix(&[Rule::Plus, Rule::Minus]))
+        .op(infix(&[Rule::Times, Rule::Divide, Rule::Modulo]))
+        .op(Op::prefix(Rule::LogicalNot) | Op::prefix(Rule::Negate) | Op::prefix(Rule::BitwiseNot));
+
+    pratt
+        .map_primary(|primary| match primary.as_rule() {
+            Rule::Expression => evaluate(primary, symbols),
+            Rule::Defined => Ok(symbols.contains_key(primary.into_inner().next().unwrap().as_str()) as u32),
+            Rule::Identifier => match symbols.get(primary.as_str()) {
+                Some(value) => Ok(*value),
+                None => Err(pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError {
+                        message: format!("undefined symbol {:?}", primary.as_str()),
+                    },
+                    primary.as_span(),
+                ))?,
+            },
+            _ => pair_to_u32(primary),
+        })
+        .map_prefix(|operator, operand| {
+            let operand = operand?;
+            Ok(match operator.as_rule() {
+                Rule::LogicalNot => (operand == 0) as u32,
+                Rule::Negate => operand.wrapping_neg(),
+                Rule::BitwiseNot => !operand,
+                _ => unreachable!(),
+            })
+        })
+        .map_infix(|lhs, operator, rhs| {
+            // an error on the right-hand side of a short-circuiting operator is ignored, so that
+            // `defined(NAME) && NAME > 1` does not fail when NAME is undefined
+            match (operator.as_rule(), &lhs) {
+                (Rule::LogicalAnd, Ok(0)) => return Ok(0),
+                (Rule::LogicalOr, Ok(lhs)) if *lhs != 0 => return Ok(1),
+                _ => {}
+            }
+            let (lhs, rhs) = (lhs?, rhs?);
+            Ok(match operator.as_rule() {
+                Rule::LogicalOr | Rule::LogicalAnd => (rhs != 0) as u32,
+                Rule::BitwiseOr => lhs | rhs,
+                Rule::BitwiseXor => lhs ^ rhs,
+                Rule::BitwiseAnd => lhs & rhs,
+                Rule::Equal => (lhs == rhs) as u32,
+                Rule::NotEqual => (lhs != rhs) as u32,
+                Rule::Less => (lhs < rhs) as u32,
+                Rule::LessEqual => (lhs <= rhs) as u32,
+                Rule::Greater => (lhs > rhs) as u32,
+                Rule::GreaterEqual => (lhs >= rhs) as u32,
+                Rule::ShiftLeft => lhs.checked_shl(rhs).unwrap_or(0),
+                Rule::ShiftRight => lhs.checked_shr(rhs).unwrap_or(0),
+                Rule::Plus => lhs.wrapping_add(rhs),
+                Rule::Minus => lhs.wrapping_sub(rhs),
+                Rule::Times => lhs.wrapping_mul(rhs),
+                Rule::Divide | Rule::Modulo if rhs == 0 => Err(pest::error::Error::new_from_span(
+                    pest::error::ErrorVariant::CustomError { message: "division by zero".into() },
+                    operator.as_span(),
+                ))?,
+                Rule::Divide => lhs / rhs,
+                Rule::Modulo => lhs % rhs,
+                _ => unreachable!(),
+            })
+        })
+        .parse(expression.into_inner())
+}
@@ -0,0 +1,15 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// The kind of program a filter is written for, which determines how its return value is interpreted
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Profile {
+    /// A socket filter attached with `SO_ATTACH_FILTER`, which returns the number of bytes of the packet to keep
+    #[default]
+    Socket,
+    /// A seccomp filter installed with `SECCOMP_SET_MODE_FILTER`, which returns a `SECCOMP_RET_*` action
+    Seccomp,
+    /// A reuseport filter attached with `SO_ATTACH_REUSEPORT_CBPF`, which returns the index of the socket that
+    /// receives the packet
+    Reuseport,
+}
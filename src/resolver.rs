@@ -0,0 +1,59 @@
+// Copyright © Alex Forster <alex@alexforster.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fs;
+use std::io;
+use std::path;
+
+/// A source file returned by a `SourceResolver`
+#[derive(Debug, Clone, Default)]
+pub struct Source {
+    /// Uniquely identifies the file; used to detect cyclic includes and to attribute errors
+    pub name: String,
+    pub text: String,
+}
+
+/// Locates the files named by `.include` directives
+pub trait SourceResolver {
+    /// Resolves `name` as written in an `.include` directive. `parent` is the name of the including `Source`, or
+    /// `None` when the directive appears in the source passed to `assemble_with_options`.
+    fn resolve(&self, name: &str, parent: Option<&str>) -> io::Result<Source>;
+}
+
+/// Resolves includes relative to the including file, then to each of `directories` in order. Without any
+/// directories, includes are resolved relative to the current working directory.
+#[derive(Debug, Clone, Default)]
+pub struct FileResolver {
+    pub directories: Vec<path::PathBuf>,
+}
+
+impl FileResolver {
+    pub fn new<I: IntoIterator<Item = P>, P: Into<path::PathBuf>>(directories: I) -> Self {
+        FileResolver { directories: directories.into_iter().map(Into::into).collect() }
+    }
+}
+
+impl SourceResolver for FileResolver {
+    fn resolve(&self, name: &str, parent: Option<&str>) -> io::Result<Source> {
+        let current_directory = path::Path::new(".");
+
+        let mut directories = Vec::default();
+        if let Some(parent_directory) = parent.and_then(|parent| path::Path::new(parent).parent()) {
+            directories.push(parent_directory);
+        }
+        directories.extend(self.directories.iter().map(path::PathBuf::as_path));
+        if self.directories.is_empty() {
+            directories.push(current_directory);
+        }
+
+        for directory in directories {
+            let candidate = directory.join(name);
+            if candidate.is_file() {
+                let candidate = fs::canonicalize(candidate)?;
+                return Ok(Source { name: candidate.to_string_lossy().into(), text: fs::read_to_string(candidate)? });
+            }
+        }
+
+        Err(io::Error::new(io::ErrorKind::NotFound, "file not found"))
+    }
+}
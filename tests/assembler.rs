@@ -92,7 +92,7 @@ fn test() {
         ],
         [
             r#"
-            ldh #proto       /* A = skb->protocol */
+            ld #proto        /* A = skb->protocol */
             
             jneq #0, skip    /* check for NETLINK_ROUTE */
             ldb [4]          /* A = nlmsg_type */
@@ -116,7 +116,7 @@ fn test() {
             ret #-1
             skip: ret #0
             "#,
-            "18,40 0 0 4294963200,21 0 15 0,48 0 0 4,21 0 13 16,1 0 0 16,80 0 0 4,21 0 10 3,0 0 0 32,1 0 0 16,32 0 0 4294963212,21 6 0 0,7 0 0 0,96 0 0 1,92 0 0 0,80 0 0 4,21 0 1 6,6 0 0 4294967295,6 0 0 0",
+            "18,32 0 0 4294963200,21 0 15 0,48 0 0 4,21 0 13 16,1 0 0 16,80 0 0 4,21 0 10 3,0 0 0 32,1 0 0 16,32 0 0 4294963212,21 6 0 0,7 0 0 0,96 0 0 1,92 0 0 0,80 0 0 4,21 0 1 6,6 0 0 4294967295,6 0 0 0",
         ],
         [
             r#"
@@ -145,10 +145,10 @@ fn test() {
             ldi #4
             ldh [4]
             ldh [x + 4]
-            ldh proto
+            ldh [0]
             ldb [4]
             ldb [x + 4]
-            ldb proto
+            ldb [0]
             ldx M[4]
             ldx 4 * ([4] & 0xF)
             ldx #4
@@ -206,8 +206,88 @@ fn test() {
             jump_target_2: ret x
             jump_target_3: ret #4
             "#,
-            "69,32 0 0 4,64 0 0 4,96 0 0 4,0 0 0 4,128 0 0 0,32 0 0 4294963200,0 0 0 4,40 0 0 4,72 0 0 4,40 0 0 4294963200,48 0 0 4,80 0 0 4,48 0 0 4294963200,97 0 0 4,177 0 0 4,1 0 0 4,129 0 0 0,1 0 0 4,177 0 0 4,2 0 0 4,3 0 0 4,5 0 0 44,5 0 0 44,29 44 42 0,29 42 0 0,21 42 40 4,21 40 0 4,29 0 40 0,29 0 37 0,21 0 37 4,21 0 37 4,61 0 34 0,53 0 34 4,45 0 34 0,37 0 31 4,45 31 0 0,37 31 0 4,61 28 0 0,61 28 0 0,77 28 26 0,77 26 0 0,69 26 24 4,69 24 0 4,12 0 0 0,4 0 0 4,28 0 0 0,20 0 0 4,44 0 0 0,36 0 0 4,60 0 0 0,52 0 0 4,156 0 0 0,148 0 0 4,132 0 0 0,92 0 0 0,84 0 0 4,76 0 0 0,68 0 0 4,172 0 0 0,164 0 0 4,108 0 0 0,100 0 0 4,124 0 0 0,116 0 0 4,7 0 0 0,135 0 0 0,22 0 0 0,14 0 0 0,6 0 0 4",
-        ]
+            "69,32 0 0 4,64 0 0 4,96 0 0 4,0 0 0 4,128 0 0 0,32 0 0 4294963200,0 0 0 4,40 0 0 4,72 0 0 4,40 0 0 0,48 0 0 4,80 0 0 4,48 0 0 0,97 0 0 4,177 0 0 4,1 0 0 4,129 0 0 0,1 0 0 4,177 0 0 4,2 0 0 4,3 0 0 4,5 0 0 44,5 0 0 44,29 44 42 0,29 42 0 0,21 42 40 4,21 40 0 4,29 0 40 0,29 0 37 0,21 0 37 4,21 0 37 4,61 0 34 0,53 0 34 4,45 0 34 0,37 0 31 4,45 31 0 0,37 31 0 4,61 28 0 0,61 28 0 0,77 28 26 0,77 26 0 0,69 26 24 4,69 24 0 4,12 0 0 0,4 0 0 4,28 0 0 0,20 0 0 4,44 0 0 0,36 0 0 4,60 0 0 0,52 0 0 4,156 0 0 0,148 0 0 4,132 0 0 0,92 0 0 0,84 0 0 4,76 0 0 0,68 0 0 4,172 0 0 0,164 0 0 4,108 0 0 0,100 0 0 4,124 0 0 0,116 0 0 4,7 0 0 0,135 0 0 0,22 0 0 0,14 0 0 0,6 0 0 4",
+        ],
+        [
+            r#"
+            ldh [12]
+            jne #0x800, drop
+            ld [30]
+            jeq #192.168.1.1, pass
+            jnet [26], 10.0.0.0/8, pass, drop
+            pass: ret #-1
+            drop: ret #0
+            "#,
+            "9,40 0 0 12,21 0 6 2048,32 0 0 30,21 3 0 3232235777,32 0 0 26,84 0 0 4278190080,21 0 1 167772160,6 0 0 4294967295,6 0 0 0",
+        ],
+        [
+            r#"
+            ld #10.1.2.3
+            jnet [x + 12], 0.0.0.0/0, pass, pass
+            jnet [16], 10.1.2.3/32, pass, pass
+            pass: ret a
+            "#,
+            "8,0 0 0 167838211,64 0 0 12,84 0 0 0,21 3 3 0,32 0 0 16,84 0 0 4294967295,21 0 0 167838211,22 0 0 0",
+        ],
+        [
+            r#"
+            .macro skip_ipv4 offset
+                ldxb 4 * ([14] & 0xf)
+                ldh [x + \offset]
+            .endm
+            .macro match_port port, target ; jump to target if the destination port matches
+                skip_ipv4 16
+                jeq #\port, \target
+                jmp out
+                out:
+            .endm
+            ldh [12]
+            jne #0x800, drop
+            match_port 22, pass
+            match_port 80, pass
+            drop: ret #0
+            pass: ret #-1
+            "#,
+            "12,40 0 0 12,21 0 8 2048,177 0 0 14,72 0 0 16,21 6 0 22,5 0 0 0,177 0 0 14,72 0 0 16,21 2 0 80,5 0 0 0,6 0 0 0,6 0 0 4294967295",
+        ],
+        [
+            r#"
+            .var ethertype
+            .macro save_x
+                .var saved
+                stx saved
+            .endm
+            ldh [12]
+            st ethertype
+            st M[0]
+            save_x
+            save_x
+            ldx ethertype
+            ld ethertype
+            ret a
+            "#,
+            "8,40 0 0 12,2 0 0 1,2 0 0 0,3 0 0 2,3 0 0 2,97 0 0 1,96 0 0 1,22 0 0 0",
+        ],
+        [
+            r#"
+            .macro jump_if target, value ; target names the caller's label even if it matches a local one
+                jeq #\value, \target
+                jmp out
+                out:
+            .endm
+            .macro jump_if_either target
+                jump_if \target, 1
+                jump_if \target, 2
+                jmp out
+                out:
+            .endm
+            ldb [0]
+            jump_if_either out
+            ret #1
+            out: ret #2
+            "#,
+            "8,48 0 0 0,21 5 0 1,5 0 0 0,21 3 0 2,5 0 0 0,5 0 0 0,6 0 0 1,6 0 0 2",
+        ],
     ];
 
     let extensions = bpfasm::extensions::linux();
@@ -221,4 +301,1073 @@ fn test() {
         );
         assert_eq!(actual, expected);
     }
+
+    // variables declared in a macro only take a slot until the expansion ends
+    let source = format!(
+        ".var keep\n.macro swap\n.var tmp\nst tmp\ntxa\nldx tmp\n.endm\n\
+         .macro nested\n.var outer\nst outer\nswap\nld outer\n.endm\n{}nested\nswap\nst keep\nret a",
+        "swap\n".repeat(17)
+    );
+    let instructions = bpfasm::assemble(source, &extensions).expect("compiler error");
+    let slots = instructions.iter().filter(|insn| matches!(insn.code, 0x02 | 0x60 | 0x61)).map(|insn| insn.k);
+    let expected = [[1, 1].repeat(17), vec![1, 2, 2, 1, 1, 1, 0]].concat();
+    assert_eq!(slots.collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_errors() {
+    let programs = [
+        ["ld #10.1.2.256\nret a", "invalid IPv4 address \"10.1.2.256\""],
+        [
+            "jnet [26], 10.1.0.0/8, drop, drop\ndrop: ret #0",
+            "host bits set in network \"10.1.0.0/8\" (did you mean 10.0.0.0/8?)",
+        ],
+        ["jnet [26], 10.0.0.0/33, drop, drop\ndrop: ret #0", "invalid prefix length \"33\""],
+        ["frob 1\nret a", "undeclared macro \"frob\""],
+        [".macro frob a, b\n.endm\nfrob 1\nret a", "macro \"frob\" takes 2 argument(s) but 1 were supplied"],
+        [".macro ld a\n.endm\nret a", "macro \"ld\" shadows an instruction"],
+        [".macro frob\nfrob\n.endm\nfrob\nret a", "recursive expansion of macro \"frob\""],
+        [".macro frob k\nldh [\\k]\n.endm\nfrob zz\nret a", "invalid operands for instruction \"ldh\""],
+        [".macro frob k\nldh [\\k]\n.endm\nfrob zz\nret a", "in expansion of macro \"frob\""],
+        [".macro frob k\nldh [\\k]\n.endm\nfrob zz\nret a", "macro \"frob\" defined here"],
+        ["st tmp\nret a", "undeclared variable \"tmp\""],
+        [".var tmp\n.var tmp\nret a", "redeclared variable \"tmp\""],
+        ["ldx top\n.var top\nret a", "variable \"top\" used before declaration"],
+        [
+            ".macro clobber\n.var t\nld #1\nst t\n.endm\nst top\nclobber\n.var top\nld top\nret a",
+            "variable \"top\" used before declaration",
+        ],
+        [".var len\nret a", "variable \"len\" shadows a keyword or extension"],
+        [".var tmp\nldb tmp\nret a", "variable \"tmp\" is a word and can only be loaded by ld or ldx"],
+        ["cop proto\nret a", "extension \"proto\" is not a coprocessor function"],
+        ["ldh poff\nret a", "extension \"poff\" cannot be loaded by ldh; it can only be loaded by ld"],
+        ["ldb #rand\nret a", "extension \"rand\" cannot be loaded by ldb; it can only be loaded by ld"],
+        [
+            ".var a0\n.var a1\n.var a2\n.var a3\n.var a4\n.var a5\n.var a6\n.var a7\n\
+             .var a8\n.var a9\n.var a10\n.var a11\n.var a12\n.var a13\n.var a14\n.var a15\nst M[3]\nret a",
+            "no free scratch memory slot for variable \"a15\"; M[0] to M[15] are all in use",
+        ],
+    ];
+
+    let extensions = bpfasm::extensions::linux();
+
+    for [source, expected] in programs {
+        let error = bpfasm::assemble(source, &extensions).expect_err("compiler success");
+        assert!(error.to_string().contains(expected), "{:?} does not contain {:?}", error.to_string(), expected);
+    }
+}
+
+struct Sources(&'static [(&'static str, &'static str)]);
+
+impl bpfasm::SourceResolver for Sources {
+    fn resolve(&self, name: &str, _parent: Option<&str>) -> std::io::Result<bpfasm::Source> {
+        match self.0.iter().find(|(path, _)| *path == name) {
+            Some((path, text)) => Ok(bpfasm::Source { name: path.to_string(), text: text.to_string() }),
+            None => Err(std::io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+#[test]
+fn test_includes() {
+    let sources = Sources(&[
+        (
+            "common.bpf",
+            r#"
+            .include "ethernet.bpf"
+            .macro skip_ipv4
+                ldxb 4 * ([14] & 0xf)
+            .endm
+            "#,
+        ),
+        (
+            "ethernet.bpf",
+            r#"
+            .macro require_ipv4 otherwise
+                ldh [12]
+                jne #0x800, \otherwise
+            .endm
+            "#,
+        ),
+        ("bad.bpf", "\nldh [x]\n"),
+        ("cycle.bpf", ".include \"cycle.bpf\"\n"),
+    ]);
+    let options = bpfasm::Options { resolver: Some(std::sync::Arc::new(sources)), ..Default::default() };
+    let extensions = bpfasm::extensions::linux();
+
+    let instructions = bpfasm::assemble_with_options(
+        r#"
+        .include "common.bpf"
+        require_ipv4 drop
+        skip_ipv4
+        ldh [x + 16]
+        jeq #22, pass, drop
+        pass: ret #-1
+        drop: ret #0
+        "#,
+        &extensions,
+        &options,
+    )
+    .expect("compiler error");
+    let actual = instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",");
+    assert_eq!(actual, "40 0 0 12,21 0 4 2048,177 0 0 14,72 0 0 16,21 0 1 22,6 0 0 4294967295,6 0 0 0");
+
+    let programs = [
+        [".include \"missing.bpf\"\nret a", "could not include \"missing.bpf\""],
+        [".include \"bad.bpf\"\nret a", "bad.bpf:2:1"],
+        [".include \"cycle.bpf\"\nret a", "cyclic include of \"cycle.bpf\""],
+    ];
+
+    for [source, expected] in programs {
+        let error = bpfasm::assemble_with_options(source, &extensions, &options).expect_err("compiler success");
+        assert!(error.to_string().contains(expected), "{:?} does not contain {:?}", error.to_string(), expected);
+    }
+
+    let error = bpfasm::assemble(".include \"common.bpf\"\nret a", &extensions).expect_err("compiler success");
+    assert!(error.to_string().contains("no source resolver was provided"));
+
+    // every pass sees the same text, even if the file changes while the program is being assembled
+    struct Growing(std::sync::atomic::AtomicUsize);
+    impl bpfasm::SourceResolver for Growing {
+        fn resolve(&self, name: &str, _: Option<&str>) -> std::io::Result<bpfasm::Source> {
+            let count = self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+            Ok(bpfasm::Source { name: name.into(), text: "ld #1\n".repeat(count) })
+        }
+    }
+    let growing = std::sync::Arc::new(Growing(std::sync::atomic::AtomicUsize::new(0)));
+    let options = bpfasm::Options { resolver: Some(growing.clone()), ..Default::default() };
+    let source = ".include \"growing.bpf\"\njeq #1, end\nld #2\nend: ret a";
+    let instructions = bpfasm::assemble_with_options(source, &extensions, &options).expect("compiler error");
+    let actual = instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",");
+    assert_eq!(actual, "0 0 0 1,21 1 0 1,0 0 0 2,22 0 0 0");
+    assert_eq!(growing.0.load(std::sync::atomic::Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_file_resolver() {
+    let directory = std::env::temp_dir().join(format!("bpfasm-test-{}", std::process::id()));
+    std::fs::create_dir_all(directory.join("lib")).unwrap();
+    std::fs::write(directory.join("filter.bpf"), ".include \"lib/ipv4.bpf\"\nret #0\n").unwrap();
+    std::fs::write(directory.join("lib/ipv4.bpf"), ".include \"ports.bpf\"\nldh [12]\n").unwrap();
+    std::fs::write(directory.join("lib/ports.bpf"), "ldb [23]\n").unwrap();
+
+    let resolver = bpfasm::FileResolver::new([&directory]);
+    let options = bpfasm::Options { resolver: Some(std::sync::Arc::new(resolver.clone())), ..Default::default() };
+    let source = bpfasm::SourceResolver::resolve(&resolver, "filter.bpf", None).unwrap();
+    let instructions =
+        bpfasm::assemble_with_options(source.text, &bpfasm::extensions::linux(), &options).expect("compiler error");
+    let actual = instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",");
+
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(actual, "48 0 0 23,40 0 0 12,6 0 0 0");
+}
+
+#[test]
+fn test_conditionals() {
+    let source = r#"
+        .ifdef MLX5
+            ld vlant
+            jneq #10, drop
+        .else
+            ldh [14]
+            jneq #10, drop
+        .endif
+        .if KERNEL >= 0x040a00 && !defined(NO_RAND)
+            ld rand
+            mod #4
+            jneq #0, drop
+        .endif
+        .if defined(PORT) && PORT == 22
+            .include "does-not-exist.bpf"
+        .endif
+        .if SNAPLEN > 0
+            pass: ret #96
+        .else
+            pass: ret #-1
+        .endif
+        drop: ret #0
+    "#;
+
+    let programs: [(&[(&str, u32)], &str); 3] = [
+        (&[("KERNEL", 0x040900), ("SNAPLEN", 96)], "40 0 0 14,21 0 1 10,6 0 0 96,6 0 0 0"),
+        (
+            &[("MLX5", 0), ("KERNEL", 0x040a00), ("SNAPLEN", 0), ("PORT", 80)],
+            "32 0 0 4294963244,21 0 4 10,32 0 0 4294963256,148 0 0 4,21 0 1 0,6 0 0 4294967295,6 0 0 0",
+        ),
+        (&[("KERNEL", 0x050000), ("NO_RAND", 1), ("SNAPLEN", 0)], "40 0 0 14,21 0 1 10,6 0 0 4294967295,6 0 0 0"),
+    ];
+
+    let extensions = bpfasm::extensions::linux();
+
+    for (symbols, expected) in programs {
+        let options = bpfasm::Options {
+            symbols: symbols.iter().map(|(name, value)| (name.to_string(), *value)).collect(),
+            ..Default::default()
+        };
+        let instructions = bpfasm::assemble_with_options(source, &extensions, &options).expect("compiler error");
+        let actual = instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",");
+        assert_eq!(actual, expected);
+    }
+
+    // labels in branches that are not taken do not hide labels of the same name outside a macro
+    let source = r#"
+        .macro check
+            .ifdef LOCAL
+                jeq #1, out
+                ret #3
+                out:
+            .else
+                jeq #1, out
+            .endif
+        .endm
+        ldb [0]
+        check
+        ret #1
+        out: ret #2
+    "#;
+    let programs: [(&[(&str, u32)], &str); 2] =
+        [(&[], "48 0 0 0,21 1 0 1,6 0 0 1,6 0 0 2"), (&[("LOCAL", 1)], "48 0 0 0,21 1 0 1,6 0 0 3,6 0 0 1,6 0 0 2")];
+    for (symbols, expected) in programs {
+        let options = bpfasm::Options {
+            symbols: symbols.iter().map(|(name, value)| (name.to_string(), *value)).collect(),
+            ..Default::default()
+        };
+        let instructions = bpfasm::assemble_with_options(source, &extensions, &options).expect("compiler error");
+        let actual = instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",");
+        assert_eq!(actual, expected);
+    }
+
+    let programs = [
+        [".if KERNEL > 1\n.endif\nret a", "undefined symbol \"KERNEL\""],
+        [".if 1 / (2 - 2)\n.endif\nret a", "division by zero"],
+        [".if 1\nret a", "expected"],
+        ["ret a\n.endif", "expected"],
+    ];
+
+    for [source, expected] in programs {
+        let error = bpfasm::assemble(source, &extensions).expect_err("compiler success");
+        assert!(error.to_string().contains(expected), "{:?} does not contain {:?}", error.to_string(), expected);
+    }
+}
+
+#[test]
+fn test_optimize() {
+    let programs = [
+        [
+            r#"
+            ldh [12]
+            jeq #0x800, ipv4, next
+            next: jmp drop
+            ipv4: ldh [12]
+            jeq #0x800, tcp, drop
+            tcp: ldb [23]
+            ja pad
+            pad: jeq #6, pass, drop
+            ldb [1]
+            pass: ret #-1
+            drop: ret #0
+            ret #0
+            "#,
+            "6,40 0 0 12,21 0 3 2048,48 0 0 23,21 0 1 6,6 0 0 4294967295,6 0 0 0",
+        ],
+        ["ld [14]\nst M[1]\nld M[1]\nldx M[1]\nld [14]\nret a", "4,32 0 0 14,2 0 0 1,97 0 0 1,22 0 0 0"],
+        ["ld rand\nld rand\nret a", "3,32 0 0 4294963256,32 0 0 4294963256,22 0 0 0"],
+        ["ldx #1\nld [x+2]\nldx #2\nld [x+2]\nld [x+2]\nret a", "5,1 0 0 1,64 0 0 2,1 0 0 2,64 0 0 2,22 0 0 0"],
+        ["ldh [12]\njeq #1, a, b\na: ldh [12]\nret a\nb: ldh [14]\nret a", "4,40 0 0 12,21 1 0 1,40 0 0 14,22 0 0 0"],
+        ["ldh [12]\njeq #1, a, a\na: ret a", "2,40 0 0 12,22 0 0 0"],
+    ];
+
+    let extensions = bpfasm::extensions::linux();
+
+    for [source, expected] in programs {
+        let mut instructions = bpfasm::assemble(source, &extensions).expect("compiler error");
+        bpfasm::optimize(&mut instructions);
+        let actual = format!(
+            "{},{}",
+            instructions.len(),
+            instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",")
+        );
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn test_optimize_global() {
+    let programs = [
+        [
+            r#"
+            ldh [12]
+            jne #0x800, notip
+            ldb [23]
+            st M[0]
+            ldh [12]
+            jeq #0x800, ip, drop
+            ip: ld M[0]
+            jeq #6, tcp, drop
+            tcp: ldh [12]
+            jeq #0x86dd, drop, pass
+            notip: jeq #0x86dd, ipv6, drop
+            ipv6: ldh [12]
+            jeq #0x800, pass, drop
+            pass: ret #-1
+            drop: ret #0
+            "#,
+            "6,40 0 0 12,21 0 3 2048,48 0 0 23,21 0 1 6,6 0 0 4294967295,6 0 0 0",
+        ],
+        ["ld #1\nadd #2\nst M[0]\nldx #3\njeq x, a, b\na: ret #1\nb: ret #2", "1,6 0 0 1"],
+        ["ld [0]\nst M[1]\nst M[2]\nld M[2]\nret a", "2,32 0 0 0,22 0 0 0"],
+        ["ldh [12]\ntax\nldh [14]\nldh [12]\ntax\njgt x, a, b\na: ret #1\nb: ret #2", "3,40 0 0 12,40 0 0 14,6 0 0 2"],
+        ["ld rand\nst M[0]\nld rand\nld M[0]\nret a", "2,32 0 0 4294963256,22 0 0 0"],
+        ["ldx #0\nld #1\ndiv x\nld #2\nret a", "5,1 0 0 0,0 0 0 1,60 0 0 0,0 0 0 2,22 0 0 0"],
+        ["ldx #7\nld #5\nld [0xfffff028]\nret a", "4,1 0 0 7,0 0 0 5,32 0 0 4294963240,22 0 0 0"],
+        ["ldx #7\nld #5\nld nla\nret a", "4,1 0 0 7,0 0 0 5,32 0 0 4294963212,22 0 0 0"],
+    ];
+
+    let extensions = bpfasm::extensions::linux();
+
+    for [source, expected] in programs {
+        let mut instructions = bpfasm::assemble(source, &extensions).expect("compiler error");
+        bpfasm::optimize_global(&mut instructions);
+        let actual = format!(
+            "{},{}",
+            instructions.len(),
+            instructions.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(",")
+        );
+        assert_eq!(actual, expected);
+    }
+}
+
+#[test]
+fn test_cfg() {
+    let source = r#"
+        ldh [12]
+        jne #0x800, drop
+        ipv4: ldb [23]
+        ldxb 4 * ([14] & 0xf)
+        ld [x + 4]
+        jset #0x10, drop, pass
+        pass: ret #-1
+        drop: ret #0
+    "#;
+
+    let extensions = bpfasm::extensions::linux();
+    let instructions = bpfasm::assemble(source, &extensions).expect("compiler error");
+
+    let blocks = bpfasm::cfg::blocks(&instructions).expect("invalid program");
+    let actual = blocks
+        .iter()
+        .map(|block| {
+            let edges = block.edges.iter().map(|edge| format!("{:?}:{}", edge.branch, edge.target)).collect::<Vec<_>>();
+            format!("{}..{} {}", block.start, block.end, edges.join(" "))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(actual, ["0..2 True:1 False:3", "2..6 True:3 False:2", "6..7 ", "7..8 "]);
+
+    let labels = [("ipv4", 2), ("pass", 6), ("drop", 7)].iter().map(|(name, pc)| (name.to_string(), *pc)).collect();
+    let dot = bpfasm::cfg::dot(&instructions, &labels, bpfasm::Profile::Socket).expect("invalid program");
+    assert_eq!(
+        dot,
+        r#"digraph program {
+    node [shape=box, fontname="monospace"];
+    block0 [label="(0) ldh [12]\l(1) jeq #0x800, ipv4, drop\l"];
+    block1 [label="ipv4:\l(2) ldb [23]\l(3) ldxb 4 * ([14] & 0xf)\l(4) ld [x + 4]\l(5) jset #0x10, drop, pass\l"];
+    block2 [label="pass:\l(6) ret #0xffffffff\laccept\l", style=filled, fillcolor="palegreen"];
+    block3 [label="drop:\l(7) ret #0\ldrop\l", style=filled, fillcolor="lightcoral"];
+    block0 -> block1 [label="true", color="darkgreen"];
+    block0 -> block3 [label="false", color="red", style="dashed"];
+    block1 -> block3 [label="true", color="darkgreen"];
+    block1 -> block2 [label="false", color="red", style="dashed"];
+}
+"#
+    );
+
+    let instructions =
+        bpfasm::assemble("ld [0]\njeq #59, kill, allow\nkill: ret #0x80000000\nallow: ret #0x7fff0000", &extensions)
+            .expect("compiler error");
+    let dot = bpfasm::cfg::dot(&instructions, &Default::default(), bpfasm::Profile::Seccomp).expect("invalid program");
+    assert!(dot.contains(r#"(2) ret #0x80000000\lkill process\l", style=filled, fillcolor="lightcoral""#));
+    assert!(dot.contains(r#"(3) ret #0x7fff0000\lallow\l", style=filled, fillcolor="palegreen""#));
+
+    assert!(bpfasm::cfg::blocks(&[bpfasm::Instruction { code: 0x15, jt: 1, jf: 0, k: 0 }]).is_none());
+}
+
+#[test]
+fn test_debug_info() {
+    let sources = Sources(&[("common.bpf", "ldh [12]\n  jne #0x800, drop\n")]);
+    let source = ".include \"common.bpf\"\n.macro proto p\n  ldb [23]\n  jne #\\p, drop\n.endm\nproto 6\n\
+                  jnet [26], 10.0.0.0/8, drop, drop\ndrop: ret #0";
+
+    let extensions = bpfasm::extensions::linux();
+    let options = bpfasm::Options { resolver: Some(std::sync::Arc::new(sources)), ..Default::default() };
+    let (instructions, debug_info) =
+        bpfasm::assemble_with_debug_info(source, &extensions, &options).expect("compiler error");
+
+    assert_eq!(debug_info.locations.len(), instructions.len());
+    let actual = debug_info
+        .locations
+        .iter()
+        .map(|location| {
+            let path = location.path.as_deref().unwrap_or("-");
+            format!("{}:{}:{} {:?}", path, location.line, location.column, location.span)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual,
+        [
+            "common.bpf:1:1 0..8",
+            "common.bpf:2:3 11..27",
+            "-:6:1 70..77",
+            "-:6:1 70..77",
+            "-:7:1 78..111",
+            "-:7:1 78..111",
+            "-:7:1 78..111",
+            "-:8:7 118..124",
+        ]
+    );
+    assert_eq!(&source[70..77], "proto 6");
+    assert_eq!(debug_info.labels, [("drop".to_string(), 7)].into_iter().collect());
+}
+
+#[test]
+fn test_listing() {
+    let sources = Sources(&[("common.bpf", "ldh [12]   ; ethertype\njne #0x800, drop\n")]);
+    let source = ".include \"common.bpf\"\n\
+                  jnet [26], 10.0.0.0/8, pass, drop ; private\n\
+                  pass: ret #-1\n\
+                  drop: ret #0\n";
+
+    let extensions = bpfasm::extensions::linux();
+    let options = bpfasm::Options { resolver: Some(std::sync::Arc::new(sources)), ..Default::default() };
+    let listing = bpfasm::listing(source, &extensions, &options).expect("compiler error");
+    assert_eq!(
+        listing,
+        "  pc  code jt jf k         code jt jf k       targets        source
+   0  0028 00 00 0000000c  40 0 0 12                         common.bpf:1: ldh [12]   ; ethertype
+   1  0015 00 04 00000800  21 0 4 2048        jt 2 jf 6      common.bpf:2: jne #0x800, drop
+   2  0020 00 00 0000001a  32 0 0 26                         2: jnet [26], 10.0.0.0/8, pass, drop ; private
+   3  0054 00 00 ff000000  84 0 0 4278190080
+   4  0015 00 01 0a000000  21 0 1 167772160   jt 5 jf 6
+   5  0006 00 00 ffffffff  6 0 0 4294967295                  3: pass: ret #-1
+   6  0006 00 00 00000000  6 0 0 0                           4: drop: ret #0
+"
+    );
+}
+
+#[test]
+fn test_assembler() {
+    let far = format!("jeq #1, far, near\nnear: {}far: ret #0", "ld #0\n".repeat(300));
+    let error = bpfasm::Assembler::default().assemble(&far).expect_err("compiler success");
+    assert!(error
+        .to_string()
+        .contains("label \"far\" is 300 instructions away, but a conditional jump can skip at most 255"));
+
+    let mut assembler = bpfasm::Assembler::default();
+    assembler.options.limits.relaxation = true;
+
+    let insns = assembler.assemble(&far).expect("compiler error");
+    assert_eq!(insns.len(), 304);
+    assert_eq!(
+        insns[..4].iter().map(|insn| insn.to_string()).collect::<Vec<_>>(),
+        ["21 0 1 1", "5 0 0 301", "5 0 0 0", "0 0 0 0"]
+    );
+
+    // relaxing the second jump pushes the first one's label out of reach
+    let cascade = format!("jeq #1, a\njeq #2, b\n{}a: ret #1\nld #0\nb: ret #2", "ld #0\n".repeat(254));
+    let insns = assembler.assemble(&cascade).expect("compiler error");
+    assert_eq!(insns.len(), 263);
+    assert_eq!(
+        insns[..6].iter().map(|insn| insn.to_string()).collect::<Vec<_>>(),
+        ["21 0 1 1", "5 0 0 258", "5 0 0 0", "21 0 1 2", "5 0 0 257", "5 0 0 0"]
+    );
+    assert_eq!(insns[260].to_string(), "6 0 0 1");
+    assert_eq!(insns[262].to_string(), "6 0 0 2");
+
+    assembler.options.limits.max_instructions = 3;
+    assert!(assembler.assemble("ld #0\nret a").is_ok());
+    let error = assembler.assemble("ld #0\nadd #1\nmul #2\nret a").expect_err("compiler success");
+    assert!(error.to_string().contains("program is 4 instructions long, but at most 3 are allowed"));
+
+    let programs = [
+        ["ld #0", "program must end with a return instruction"],
+        ["cop #1\nret #0", "the Linux dialect has no coprocessor"],
+        ["ret x", "the Linux dialect cannot return x"],
+        ["div #0\nret a", "division by zero"],
+        ["lsh #32\nret a", "shift by 32 or more bits"],
+    ];
+    let mut assembler = bpfasm::Assembler::default();
+    assembler.options.dialect = Some(bpfasm::Dialect::Linux);
+    for [source, expected] in programs {
+        let error = assembler.assemble(source).expect_err("compiler success");
+        assert!(error.to_string().contains(expected), "{:?} does not contain {:?}", error.to_string(), expected);
+    }
+
+    let mut assembler = bpfasm::Assembler::default();
+    assembler.options.profile = bpfasm::Profile::Seccomp;
+    assembler.options.symbols.insert("AUDIT_ARCH_X86_64".to_string(), 0xc000003e);
+    let source = ".if AUDIT_ARCH_X86_64\n\
+                  ld [4]\n\
+                  jne #0xc000003e, kill\n\
+                  .endif\n\
+                  ld [0]\n\
+                  ret #0x7fff0000\n\
+                  kill: ret #0";
+    assert!(assembler.assemble(source).is_ok());
+    for source in ["ldh [0]\nret a", "ld [64]\nret a", "ld [2]\nret a", "ld proto\nret a", "mod #3\nret a"] {
+        let error = assembler.assemble(source).expect_err("compiler success");
+        assert!(error.to_string().contains("is not allowed in a seccomp filter"), "{}", error);
+    }
+
+    // one configured assembler, resolver included, can be shared between threads
+    fn shared<T: Send + Sync + 'static>(value: T) -> T {
+        value
+    }
+    let mut assembler = shared(bpfasm::Assembler::default());
+    assembler.options.resolver = Some(std::sync::Arc::new(Sources(&[("drop.bpf", "ret #0\n")])));
+    let assembler = std::sync::Arc::new(assembler);
+    let thread = std::thread::spawn({
+        let assembler = assembler.clone();
+        move || assembler.assemble(".include \"drop.bpf\"").expect("compiler error")
+    });
+    assert_eq!(thread.join().unwrap(), assembler.assemble("ret #0").expect("compiler error"));
+}
+
+struct Registers;
+
+impl bpfasm::extensions::ExtensionResolver for Registers {
+    fn resolve(&self, name: &str) -> Option<bpfasm::extensions::Extension> {
+        let index = name.strip_prefix('r')?.parse::<u32>().ok()?;
+        Some(bpfasm::extensions::Extension {
+            offset: 0x1000 + 4 * index,
+            widths: vec![bpfasm::extensions::Width::Word],
+            cop: false,
+            code: None,
+            min_kernel: None,
+            description: format!("register {}", index),
+        })
+    }
+}
+
+#[test]
+fn test_extension_resolver() {
+    let insns = bpfasm::assemble(
+        "ld r0
+ld r7
+ret a",
+        &Registers,
+    )
+    .expect("compiler error");
+    assert_eq!(
+        insns.iter().map(|insn| insn.to_string()).collect::<Vec<_>>(),
+        ["32 0 0 4096", "32 0 0 4124", "22 0 0 0"]
+    );
+    let error = bpfasm::assemble("ld rx\nret a", &Registers).expect_err("compiler success");
+    assert!(error.to_string().contains("invalid extension \"rx\""));
+
+    let extensions = bpfasm::extensions::linux_table();
+    let proto = extensions.get("proto").expect("missing extension");
+    assert_eq!((proto.offset, proto.min_kernel), (0xfffff000, Some((2, 4, 0))));
+    assert_eq!(extensions["vlan_tpid"].min_kernel, Some((4, 4, 0)));
+    let linux: std::collections::HashMap<String, u32> = bpfasm::extensions::linux();
+    assert_eq!(linux.len(), extensions.len());
+    assert!(extensions.iter().all(|(name, extension)| linux[name] == extension.offset));
+    for name in ["proto", "net_off", "ll_off"] {
+        let error = bpfasm::assemble(format!("cop {}\nret a", name), &linux).expect_err("compiler success");
+        assert!(error.to_string().contains("is not a coprocessor function"), "{}", error);
+    }
+
+    let old = bpfasm::extensions::linux_kernel((3, 10, 0));
+    assert!(old.contains_key("vlant") && old.contains_key("alu_xor_x"));
+    assert!(!old.contains_key("poff") && !old.contains_key("rand") && !old.contains_key("vlan_tpid"));
+    let error = bpfasm::assemble("ld rand\nret a", &old).expect_err("compiler success");
+    assert!(error.to_string().contains("invalid extension \"rand\""));
+    let insns = bpfasm::assemble("ldb net_off\nldh ll_off\nret a", &old).expect("compiler error");
+    assert_eq!((insns[0].k, insns[1].k), (0xfff00000, 0xffe00000));
+
+    // plain tables of k values still work, and their entries can be called by cop
+    let table = std::collections::HashMap::from([("hash".to_string(), 3u32), ("pto".to_string(), 0xfffff000)]);
+    let insns = bpfasm::assemble("ldb hash\ncop hash\nret a", &table).expect("compiler error");
+    let error = bpfasm::assemble("ldh pto\nret a", &table).expect_err("compiler success");
+    assert!(error.to_string().contains("extension \"pto\" cannot be loaded by ldh; it can only be loaded by ld"));
+    assert_eq!(insns.iter().map(|insn| insn.to_string()).collect::<Vec<_>>(), ["48 0 0 3", "39 0 0 3", "22 0 0 0"]);
+
+    let assembler = bpfasm::Assembler { extensions: std::sync::Arc::new(Registers), ..Default::default() };
+    assert_eq!(assembler.assemble("ld r1\nret a").expect("compiler error")[0].k, 0x1004);
+}
+
+#[test]
+fn test_dialects() {
+    let assemblers = [
+        (bpfasm::Dialect::Linux, bpfasm::extensions::linux_table()),
+        (bpfasm::Dialect::FreeBSD, bpfasm::extensions::freebsd()),
+        (bpfasm::Dialect::OpenBSD, bpfasm::extensions::openbsd()),
+        (bpfasm::Dialect::NetBSD, bpfasm::extensions::netbsd()),
+    ]
+    .map(|(dialect, extensions)| {
+        let options = bpfasm::Options { dialect: Some(dialect), ..Default::default() };
+        bpfasm::Assembler { extensions: std::sync::Arc::new(extensions), options }
+    });
+    let [linux, freebsd, openbsd, netbsd] = &assemblers;
+
+    let insns = openbsd.assemble("ld rnd\nret a").expect("compiler error");
+    assert_eq!(insns.iter().map(|insn| insn.to_string()).collect::<Vec<_>>(), ["192 0 0 0", "22 0 0 0"]);
+    let insns = netbsd.assemble("ld #1\ncop npf_cop_table\ncopx\nret a").expect("compiler error");
+    assert_eq!(
+        insns.iter().map(|insn| insn.to_string()).collect::<Vec<_>>(),
+        ["0 0 0 1", "39 0 0 1", "71 0 0 0", "22 0 0 0"]
+    );
+    assert!(freebsd.assemble("lsh #32\nret a").is_ok());
+
+    let programs = [
+        (linux, "ld proto\nret a", ""),
+        (freebsd, "ld proto\nret a", "invalid extension \"proto\""),
+        (openbsd, "ldh rnd\nret a", "extension \"rnd\" cannot be loaded by ldh; it can only be loaded by ld"),
+        (netbsd, "ld npf_cop_l3\nret a", "extension \"npf_cop_l3\" cannot be loaded"),
+        (freebsd, "ld [0xfffff000]\nret a", "the FreeBSD dialect has no ancillary data area"),
+        (openbsd, "ldb [0xfff00000]\nret a", "the OpenBSD dialect has no ancillary data area"),
+        (freebsd, "cop #1\nret a", "the FreeBSD dialect has no coprocessor"),
+        (netbsd, "ret x", "the NetBSD dialect cannot return x"),
+        (netbsd, "div #0\nret a", "division by zero"),
+        (openbsd, "ld #0", "program must end with a return instruction"),
+    ];
+    for (assembler, source, expected) in programs {
+        match expected {
+            "" => assert!(assembler.assemble(source).is_ok(), "{:?}", source),
+            expected => {
+                let error = assembler.assemble(source).expect_err("compiler success");
+                assert!(
+                    error.to_string().contains(expected),
+                    "{:?} does not contain {:?}",
+                    error.to_string(),
+                    expected
+                );
+            }
+        }
+    }
+
+    let mut rnd =
+        bpfasm::Assembler { extensions: std::sync::Arc::new(bpfasm::extensions::openbsd()), ..Default::default() };
+    rnd.options.dialect = Some(bpfasm::Dialect::Linux);
+    let error = rnd.assemble("ld rnd\nret a").expect_err("compiler success");
+    assert!(error.to_string().contains("the Linux dialect has no random number load"));
+}
+
+#[test]
+fn test_default_dialect() {
+    let assembler = bpfasm::Assembler::default();
+    for source in ["ret x", "cop #1\nret #0", "ld #1\nlsh #32\nret a", "ld [0xfffff000]\nret a", "ld proto\nret a"] {
+        let insns = assembler.assemble(source).expect("compiler error");
+        assert_eq!(bpfasm::assemble(source, &bpfasm::extensions::linux()).expect("compiler error"), insns);
+    }
+}
+
+#[test]
+fn test_build() {
+    let root = std::env::temp_dir().join(format!("bpfasm-build-{}", std::process::id()));
+    let filters = root.join("filters");
+    std::fs::create_dir_all(&filters).expect("create directory");
+    std::fs::write(filters.join("ethernet.inc"), "ldh [12]\njne #0x800, drop\n").expect("write file");
+    std::fs::write(
+        filters.join("ipv4-tcp.bpf"),
+        ".include \"ethernet.inc\"\nldb [23]\njneq #6, drop\nret #-1\ndrop: ret #0\n",
+    )
+    .expect("write file");
+    std::fs::write(filters.join("notes.txt"), "not a filter").expect("write file");
+    std::env::set_var("OUT_DIR", &root);
+
+    let output = bpfasm::build::directory_with(&filters, &bpfasm::Assembler::default()).expect("build error");
+    assert_eq!(output, root.join("filters.rs"));
+    let generated = std::fs::read_to_string(&output).expect("read file");
+    assert_eq!(
+        generated.lines().skip(1).collect::<Vec<_>>(),
+        [
+            "",
+            "/// Assembled from `ipv4-tcp.bpf`",
+            "pub const IPV4_TCP: [::bpfasm::Instruction; 6] = [",
+            "    ::bpfasm::Instruction { code: 40, jt: 0, jf: 0, k: 12 },                     // ldh [12]",
+            "    ::bpfasm::Instruction { code: 21, jt: 0, jf: 3, k: 2048 },                   // jeq #0x800, 2, drop",
+            "    ::bpfasm::Instruction { code: 48, jt: 0, jf: 0, k: 23 },                     // ldb [23]",
+            "    ::bpfasm::Instruction { code: 21, jt: 0, jf: 1, k: 6 },                      // jeq #6, 4, drop",
+            "    ::bpfasm::Instruction { code: 6, jt: 0, jf: 0, k: 4294967295 },              // ret #0xffffffff",
+            "    ::bpfasm::Instruction { code: 6, jt: 0, jf: 0, k: 0 },                       // ret #0",
+            "];",
+        ]
+    );
+
+    std::fs::write(filters.join("broken.bpf"), "ldh [12]\njne #0x800, nowhere\n").expect("write file");
+    let error = bpfasm::build::directory_with(&filters, &bpfasm::Assembler::default()).expect_err("build success");
+    let error = error.to_string();
+    assert!(error.contains("broken.bpf:2:13") && error.contains("undeclared label \"nowhere\""), "{}", error);
+
+    std::fs::remove_dir_all(&root).expect("remove directory");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Filter {
+        #[serde(with = "bpfasm::mnemonic::program")]
+        program: Vec<bpfasm::Instruction>,
+    }
+
+    let insns = bpfasm::assemble(
+        "ldh [12]\njne #0x800, drop\nldxb 4 * ([14] & 0xf)\nld [x + 16]\nadd x\nret #-1\ndrop: ret #0\n",
+        &bpfasm::extensions::linux(),
+    )
+    .expect("assembler error");
+
+    let numeric = serde_json::to_string(&insns[..2]).expect("serialize error");
+    assert_eq!(numeric, r#"[{"code":40,"jt":0,"jf":0,"k":12},{"code":21,"jt":0,"jf":4,"k":2048}]"#);
+    assert_eq!(serde_json::from_str::<Vec<bpfasm::Instruction>>(&numeric).expect("deserialize error"), insns[..2]);
+
+    let filter = Filter { program: insns };
+    let mnemonic = serde_json::to_string(&filter).expect("serialize error");
+    assert_eq!(
+        mnemonic,
+        r##"{"program":["ldh [12]","jeq #0x800, 0, 4","ldxb 4 * ([14] & 0xf)","ld [x + 16]","add x","ret #0xffffffff","ret #0"]}"##
+    );
+    assert_eq!(serde_json::from_str::<Filter>(&mnemonic).expect("deserialize error"), filter);
+
+    // every instruction survives a round trip, including those that have no mnemonic
+    for code in 0..=u16::MAX {
+        let insn = bpfasm::Instruction { code, jt: 1, jf: 2, k: 0x1234 };
+        let filter = Filter { program: vec![insn] };
+        let text = serde_json::to_string(&filter).expect("serialize error");
+        assert_eq!(serde_json::from_str::<Filter>(&text).expect("deserialize error"), filter, "{}", text);
+    }
+
+    let error = serde_json::from_str::<Filter>(r#"{"program":["ldh [12]","jmp drop"]}"#).expect_err("deserialized");
+    assert!(error.to_string().contains("invalid instruction \"jmp drop\""), "{}", error);
+}
+
+#[test]
+fn test_codegen() {
+    let source = "ldh [12]   ; ethertype */\njne #0x800, drop\nret #-1\ndrop: ret #0\n";
+    let (insns, debug_info) = bpfasm::Assembler::default().assemble_with_debug_info(source).expect("assembler error");
+    let options = bpfasm::codegen::Options {
+        name: "ipv4".to_string(),
+        comments: Some(bpfasm::codegen::comments(source, &debug_info)),
+    };
+
+    assert_eq!(
+        bpfasm::codegen::go(&insns, &options).lines().collect::<Vec<_>>(),
+        [
+            "var ipv4 = []bpf.RawInstruction{",
+            "\t{Op: 0x0028, Jt: 0x00, Jf: 0x00, K: 0x0000000c}, // 1: ldh [12]   ; ethertype */",
+            "\t{Op: 0x0015, Jt: 0x00, Jf: 0x01, K: 0x00000800}, // 2: jne #0x800, drop",
+            "\t{Op: 0x0006, Jt: 0x00, Jf: 0x00, K: 0xffffffff}, // 3: ret #-1",
+            "\t{Op: 0x0006, Jt: 0x00, Jf: 0x00, K: 0x00000000}, // 4: drop: ret #0",
+            "}",
+        ]
+    );
+
+    assert_eq!(
+        bpfasm::codegen::c(&insns[..2], &options).lines().collect::<Vec<_>>(),
+        [
+            "static struct sock_filter ipv4[] = {",
+            "\t{ 0x0028, 0x00, 0x00, 0x0000000c }, /* 1: ldh [12]   ; ethertype * / */",
+            "\t{ 0x0015, 0x00, 0x01, 0x00000800 }, /* 2: jne #0x800, drop */",
+            "};",
+            "",
+            "static struct sock_fprog ipv4_prog = {",
+            "\t.len = sizeof(ipv4) / sizeof(ipv4[0]),",
+            "\t.filter = ipv4,",
+            "};",
+        ]
+    );
+
+    let rust = bpfasm::codegen::rust(&insns[..1], &bpfasm::codegen::Options::default());
+    assert_eq!(
+        rust,
+        "pub const FILTER: [libc::sock_filter; 1] = [\n    \
+             libc::sock_filter { code: 0x0028, jt: 0x00, jf: 0x00, k: 0x0000000c },\n\
+         ];\n"
+    );
+
+    // statements that produce several instructions are commented once
+    let source = "jnet [26], 10.0.0.0/8, accept, drop\naccept: ret #-1\ndrop: ret #0\n";
+    let (insns, debug_info) = bpfasm::Assembler::default().assemble_with_debug_info(source).expect("assembler error");
+    let comments = bpfasm::codegen::comments(source, &debug_info);
+    assert!(insns.len() > 3);
+    assert_eq!(comments.iter().filter(|comment| !comment.is_empty()).collect::<Vec<_>>().len(), 3);
+    assert_eq!(comments[0], "1: jnet [26], 10.0.0.0/8, accept, drop");
+}
+
+#[test]
+fn test_elf() {
+    let source = "ldh [12]\njne #0x800, drop\nret #-1\ndrop: ret #0\n";
+    let (insns, debug_info) = bpfasm::Assembler::default().assemble_with_debug_info(source).expect("assembler error");
+    let mut labels = debug_info.labels;
+    labels.insert("label@1".to_string(), 0);
+    let options = bpfasm::elf::Options { name: "ipv4".to_string(), labels, big_endian: false, ..Default::default() };
+    let object = bpfasm::elf::object(&insns, &options);
+
+    let u16_at = |offset: usize| u16::from_le_bytes(object[offset..offset + 2].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_le_bytes(object[offset..offset + 8].try_into().unwrap());
+    assert_eq!(object[..6], *b"\x7fELF\x02\x01");
+    assert_eq!(u16_at(16), 1); // ET_REL
+
+    // section 1 holds the array followed by its length
+    let section = u64_at(40) as usize + 64;
+    let (offset, size) = (u64_at(section + 24) as usize, u64_at(section + 32) as usize);
+    assert_eq!(
+        object[offset..offset + size],
+        [
+            0x28, 0, 0, 0, 12, 0, 0, 0, 0x15, 0, 0, 1, 0, 8, 0, 0, 6, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 6, 0, 0, 0, 0,
+            0, 0, 0, 4, 0, 0, 0
+        ]
+    );
+
+    let strings = String::from_utf8_lossy(&object);
+    for name in ["\0ipv4\0", "\0ipv4_len\0", "\0ipv4_drop\0", "\0.rodata.bpf\0", "\0.symtab\0"] {
+        assert!(strings.contains(name), "{:?}", name);
+    }
+    assert!(!strings.contains("label@1"));
+
+    // a label whose symbol would clash with the length is skipped
+    let source = "jeq #0, len\nret #1\nlen: ret #0\n";
+    let (insns, debug_info) = bpfasm::Assembler::default().assemble_with_debug_info(source).expect("assembler error");
+    let options = bpfasm::elf::Options { name: "ipv4".to_string(), labels: debug_info.labels, ..Default::default() };
+    let object = bpfasm::elf::object(&insns, &options);
+    assert_eq!(object.windows(10).filter(|window| *window == b"\0ipv4_len\0").count(), 1);
+}
+
+#[test]
+fn test_ebpf() {
+    let source = "ldb [9]\njeq #6, tcp, drop\ntcp: ld proto\nst M[1]\nldx #0\ndiv x\nret #-1\ndrop: ret #0\n";
+    let insns = bpfasm::Assembler::default().assemble(source).expect("assembler error");
+    let program = bpfasm::ebpf::convert(&insns).expect("convert error");
+    let encoded = program.iter().map(|insn| (insn.code, insn.dst, insn.src, insn.off, insn.imm)).collect::<Vec<_>>();
+    assert_eq!(
+        encoded,
+        [
+            (0xb4, 0, 0, 0, 0),    // w0 = 0
+            (0xb4, 7, 0, 0, 0),    // w7 = 0
+            (0xbf, 6, 1, 0, 0),    // r6 = r1
+            (0x30, 0, 0, 0, 9),    // r0 = *(u8 *)skb[9]
+            (0x55, 0, 0, 10, 6),   // if r0 != 6 goto drop
+            (0x61, 0, 6, 16, 0),   // r0 = skb->protocol
+            (0xdc, 0, 0, 0, 16),   // r0 = be16 r0
+            (0x63, 10, 0, -60, 0), // *(u32 *)(r10 - 60) = w0
+            (0xb4, 7, 0, 0, 0),    // w7 = 0
+            (0x55, 7, 0, 2, 0),    // if r7 != 0 goto +2
+            (0xac, 0, 0, 0, 0),    // w0 ^= w0
+            (0x95, 0, 0, 0, 0),    // exit
+            (0x3c, 0, 7, 0, 0),    // w0 /= w7
+            (0xb4, 0, 0, 0, -1),   // w0 = -1
+            (0x95, 0, 0, 0, 0),    // exit
+            (0xb4, 0, 0, 0, 0),    // drop: w0 = 0
+            (0x95, 0, 0, 0, 0),    // exit
+        ]
+    );
+    assert_eq!(bpfasm::ebpf::bytecode(&program[2..3]), [0xbf, 0x16, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(program[7].to_bytes(true), [0x63, 0xa0, 0xff, 0xc4, 0, 0, 0, 0]);
+
+    // comparisons against immediates with the top bit set are made against a register
+    let insns = bpfasm::Assembler::default().assemble("jgt #0x80000000, big\nret #0\nbig: ret #1\n").unwrap();
+    let program = bpfasm::ebpf::convert(&insns).expect("convert error");
+    assert_eq!((program[3].code, program[3].dst, program[3].imm), (0xb4, 2, i32::MIN));
+    assert_eq!((program[4].code, program[4].src, program[4].off, program[4].imm), (0x2d, 2, 2, 0));
+
+    let insns = bpfasm::Assembler::default().assemble("ld hatype\nret a\n").expect("assembler error");
+    let error = bpfasm::ebpf::convert(&insns).expect_err("converted");
+    assert_eq!(error.pc, 0);
+    assert_eq!(error.to_string(), "instruction 0 (ld [4294963228]) cannot be converted to eBPF: unsupported extension");
+
+    let options = bpfasm::elf::Options { section: "socket".to_string(), big_endian: false, ..Default::default() };
+    let object = bpfasm::elf::ebpf_object(&program, &options);
+    assert_eq!(object[..6], *b"\x7fELF\x02\x01");
+    assert_eq!(u16::from_le_bytes([object[18], object[19]]), 247); // EM_BPF
+}
+
+#[test]
+fn test_interpreter() {
+    let source =
+        "ldh [12]\njne #0x800, drop\nldb [23]\njne #6, drop\nldxb 4*([14]&0xf)\nldh [x+16]\nret a\ndrop: ret #0\n";
+    let insns = bpfasm::Assembler::default().assemble(source).expect("assembler error");
+    bpfasm::interpreter::check(&insns).expect("check error");
+
+    let mut packet = [0u8; 54];
+    packet[12..14].copy_from_slice(&[0x08, 0x00]);
+    packet[14] = 0x45;
+    packet[23] = 6;
+    packet[36..38].copy_from_slice(&[0x01, 0xbb]);
+    assert_eq!(bpfasm::interpreter::run(&insns, &packet), 443);
+    assert_eq!(bpfasm::interpreter::run(&insns, &packet[..37]), 0);
+    packet[23] = 17;
+    assert_eq!(bpfasm::interpreter::run(&insns, &packet), 0);
+
+    let insns = bpfasm::Assembler::default().assemble("ld len\nst M[3]\nldx #0\nld M[3]\nret a\n").unwrap();
+    assert_eq!(bpfasm::interpreter::run(&insns, &packet), 54);
+    let insns = bpfasm::Assembler::default().assemble("ld #7\nldx #0\ndiv x\nret #1\n").unwrap();
+    assert_eq!(bpfasm::interpreter::run(&insns, &packet), 0);
+
+    let insn = |code, k| bpfasm::Instruction { code, jt: 0, jf: 0, k };
+    for (insns, expected) in [
+        (
+            vec![insn(0x16, 0), insn(0x00, 1)],
+            "instruction 1 cannot be run: the program does not end with a return instruction",
+        ),
+        (vec![insn(0x34, 0), insn(0x16, 0)], "instruction 0 cannot be run: division by zero"),
+        (vec![insn(0x64, 32), insn(0x16, 0)], "instruction 0 cannot be run: shift by 32 or more bits"),
+        (vec![insn(0x60, 16), insn(0x16, 0)], "instruction 0 cannot be run: unsupported instruction"),
+        (vec![insn(0x20, 0xfffff000), insn(0x16, 0)], "instruction 0 cannot be run: ancillary data is not available"),
+        (
+            vec![bpfasm::Instruction { code: 0x15, jt: 1, jf: 0, k: 0 }, insn(0x06, 0)],
+            "instruction 0 cannot be run: jump out of range",
+        ),
+    ] {
+        assert_eq!(bpfasm::interpreter::check(&insns).expect_err("checked").to_string(), expected, "{:?}", insns);
+    }
+    assert_eq!(bpfasm::interpreter::check(&[]).expect_err("checked").reason, "the program is empty");
+}
+
+#[cfg(all(feature = "jit", target_arch = "x86_64", unix))]
+#[test]
+fn test_jit() {
+    // xorshift64, so that failures can be reproduced
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut random = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as u32
+    };
+
+    for _ in 0..2000 {
+        let length = 2 + random() as usize % 40;
+        let mut insns = Vec::with_capacity(length);
+        for pc in 0..length - 1 {
+            let k = match random() % 3 {
+                0 => random() % 8,
+                1 => random() % 70,
+                _ => random(),
+            };
+            let remaining = (length - 2 - pc) as u32;
+            let offset = |value: u32| (value % (remaining + 1)).min(255) as u8;
+            let (code, jt, jf, k) = match random() % 9 {
+                0 => ([0x20, 0x28, 0x30, 0x40, 0x48, 0x50][random() as usize % 6], 0, 0, k % 70),
+                1 => ([0x00, 0x80, 0x01, 0x81][random() as usize % 4], 0, 0, k),
+                2 => ([0x60, 0x61, 0x02, 0x03][random() as usize % 4], 0, 0, k % 16),
+                3 => (0xb1, 0, 0, k % 70),
+                4 | 5 => {
+                    let op = [0x00, 0x10, 0x20, 0x30, 0x40, 0x50, 0x60, 0x70, 0x80, 0x90, 0xa0][random() as usize % 11];
+                    match (op, random() % 2) {
+                        (0x80, _) => (0x84, 0, 0, 0),
+                        (_, 1) => (0x04 | op | 0x08, 0, 0, 0),
+                        (0x30 | 0x90, _) => (0x04 | op, 0, 0, k.max(1)),
+                        (0x60 | 0x70, _) => (0x04 | op, 0, 0, k % 32),
+                        _ => (0x04 | op, 0, 0, k),
+                    }
+                }
+                6 => (0x05, 0, 0, random() % (remaining + 1)),
+                7 => {
+                    let op = [0x15, 0x25, 0x35, 0x45, 0x1d, 0x2d, 0x3d, 0x4d][random() as usize % 8];
+                    (op, offset(random()), offset(random()), k)
+                }
+                _ => ([0x07, 0x87, 0x06, 0x16][random() as usize % 4], 0, 0, k),
+            };
+            insns.push(bpfasm::Instruction { code, jt, jf, k });
+        }
+        insns.push(bpfasm::Instruction { code: [0x06, 0x16][random() as usize % 2], jt: 0, jf: 0, k: random() });
+
+        let jit = bpfasm::jit::Jit::compile(&insns).unwrap_or_else(|error| panic!("{}: {:?}", error, insns));
+        for _ in 0..16 {
+            let packet = (0..random() % 65).map(|_| random() as u8).collect::<Vec<_>>();
+            let expected = bpfasm::interpreter::run(&insns, &packet);
+            assert_eq!(jit.run(&packet), expected, "{:?} over {:?}", insns, packet);
+        }
+    }
+
+    let insns = bpfasm::Assembler::default().assemble("ld proto\nret a\n").unwrap();
+    assert!(matches!(bpfasm::jit::Jit::compile(&insns), Err(bpfasm::jit::JitError::Check(_))));
+}
+
+#[test]
+fn test_pcap() {
+    let ipv4 = |protocol: u8| {
+        let mut packet = vec![0x45, 0, 0, 40, 0, 0, 0, 0, 64, protocol, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2];
+        packet.extend_from_slice(&[0x04, 0xd2, 0x01, 0xbb]);
+        packet.resize(40, 0);
+        packet
+    };
+    let ethernet = |tags: &[u8], payload: Vec<u8>| {
+        let mut frame = [[0xff; 6], [0x02; 6]].concat();
+        frame.extend_from_slice(tags);
+        frame.extend_from_slice(&[0x08, 0x00]);
+        frame.extend(payload);
+        frame
+    };
+    let frames = [ethernet(&[], ipv4(6)), ethernet(&[], ipv4(17)), ethernet(&[0x81, 0x00, 0x00, 0x05], ipv4(6))];
+
+    let mut pcap = Vec::default();
+    for value in [0xa1b2c3d4u32, 0x00040002, 0, 0, 65535, 1] {
+        pcap.extend_from_slice(&value.to_le_bytes());
+    }
+    for (index, frame) in frames.iter().enumerate() {
+        for value in [1_700_000_000 + index as u32, 250_000, frame.len() as u32, 1500] {
+            pcap.extend_from_slice(&value.to_le_bytes());
+        }
+        pcap.extend_from_slice(frame);
+    }
+
+    let source = "ldh [12]\njne #0x800, drop\nldb [23]\njne #6, drop\nret #20\ndrop: ret #0\n";
+    let insns = bpfasm::Assembler::default().assemble(source).expect("assembler error");
+    let mut output = Vec::default();
+    let options = bpfasm::pcap::Options::default();
+    let summary = bpfasm::pcap::run(&insns, &pcap[..], Some(&mut output), &options).expect("pcap error");
+    assert_eq!((summary.accepted, summary.dropped), (1, 2));
+    assert_eq!(summary.packets.iter().map(|packet| packet.result).collect::<Vec<_>>(), [20, 0, 0]);
+    assert_eq!(summary.packets[0].number, 1);
+    assert_eq!(summary.packets[0].timestamp, std::time::Duration::new(1_700_000_000, 250_000_000));
+    assert_eq!((summary.packets[0].length, summary.packets[0].captured), (1500, 54));
+
+    // accepted packets are truncated to the result
+    let records = bpfasm::pcap::Reader::new(&output[..]).expect("pcap error").collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].data, frames[0][..20]);
+    assert_eq!((records[0].link_type, records[0].length), (1, 1500));
+    assert_eq!(records[0].timestamp, std::time::Duration::new(1_700_000_000, 250_000_000));
+
+    // big-endian pcapng with nanosecond timestamps, an unknown block, and programs that start at the network layer
+    let block = |kind: u32, mut body: Vec<u8>| {
+        body.resize(body.len().next_multiple_of(4), 0);
+        let length = (body.len() as u32 + 12).to_be_bytes();
+        [&kind.to_be_bytes()[..], &length, &body, &length].concat()
+    };
+    let mut pcapng = block(0x0a0d0d0a, [&0x1a2b3c4du32.to_be_bytes()[..], &[0, 1, 0, 0], &[0xff; 8]].concat());
+    pcapng.extend(block(1, [&[0, 1, 0, 0, 0, 0, 0, 0][..], &[0, 9, 0, 1, 9, 0, 0, 0], &[0; 4]].concat()));
+    pcapng.extend(block(0xbad, vec![1, 2, 3]));
+    for frame in frames.iter() {
+        let time = 1_700_000_000_000_000_007u64;
+        let header = [0, (time >> 32) as u32, time as u32, frame.len() as u32, frame.len() as u32];
+        let header = header.iter().flat_map(|value| value.to_be_bytes()).collect::<Vec<_>>();
+        pcapng.extend(block(6, [header, frame.clone()].concat()));
+    }
+
+    let insns = bpfasm::Assembler::default().assemble("ldb [9]\njne #6, drop\nret #-1\ndrop: ret #0\n").unwrap();
+    let options = bpfasm::pcap::Options { layer: bpfasm::pcap::Layer::Network };
+    let summary = bpfasm::pcap::run(&insns, &pcapng[..], None, &options).expect("pcap error");
+    assert_eq!(summary.packets.iter().map(|packet| packet.result).collect::<Vec<_>>(), [u32::MAX, 0, u32::MAX]);
+    assert_eq!(summary.packets[2].captured, 40);
+    assert_eq!(summary.packets[2].timestamp, std::time::Duration::new(1_700_000_000, 7));
+
+    let summary = bpfasm::pcap::run(&insns, &pcapng[..], None, &Default::default()).expect("pcap error");
+    assert_eq!((summary.accepted, summary.dropped), (0, 3));
+
+    // a raw IPv4 packet from a second interface cannot be written after Ethernet frames
+    pcapng.extend(block(1, vec![0, 101, 0, 0, 0, 0, 0, 0]));
+    let header = [1u32, 0, 0, 40, 40].iter().flat_map(|value| value.to_be_bytes()).collect::<Vec<_>>();
+    pcapng.extend(block(6, [header, ipv4(6)].concat()));
+    let summary = bpfasm::pcap::run(&insns, &pcapng[..], None, &options).expect("pcap error");
+    assert_eq!(summary.packets.iter().map(|packet| packet.result).collect::<Vec<_>>()[3], u32::MAX);
+    let mut output = Vec::default();
+    let error = bpfasm::pcap::run(&insns, &pcapng[..], Some(&mut output), &options).expect_err("ran");
+    assert_eq!(error.to_string(), "packet 4 has link type 101, but the output file has link type 1");
+
+    let error = bpfasm::pcap::run(&insns, &pcap[..30], None, &Default::default()).expect_err("ran");
+    assert_eq!(error.to_string(), "unexpected end of file");
+    let error = bpfasm::pcap::run(&insns, &b"ldb [9]\n"[..], None, &Default::default()).expect_err("ran");
+    assert_eq!(error.to_string(), "invalid capture file: unknown magic number 0x6c646220");
 }